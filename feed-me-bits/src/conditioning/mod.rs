@@ -0,0 +1,75 @@
+//! Conditioning steps that turn raw device output into usable entropy.
+
+use sha2::{Digest, Sha256};
+use sha3::Shake256;
+use sha3::digest::{ExtendableOutputReset, Update, XofReader};
+
+/// Von Neumann debiasing. Output length varies with the input data; see
+/// `extractor::von_neumann`.
+pub use crate::extractor::von_neumann;
+
+/// SHA-256 conditioning function in the style of the SP 800-90A Hash_df.
+///
+/// Input is accumulated with `absorb`. `extract` hashes everything absorbed
+/// so far into a seed, then stretches it to the requested length by hashing
+/// `counter || seed` for counter = 1, 2, ... Extracting consumes the input,
+/// so the next call starts from an empty pool.
+///
+/// Output never carries more entropy than was absorbed: feed at least as
+/// many raw bytes as you extract, and more if the source is biased.
+#[derive(Debug, Clone, Default)]
+pub struct Sha256Conditioner {
+    hasher: Sha256,
+}
+
+impl Sha256Conditioner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn absorb(&mut self, data: &[u8]) {
+        Digest::update(&mut self.hasher, data);
+    }
+
+    pub fn extract(&mut self, out_len: usize) -> Vec<u8> {
+        let seed = self.hasher.finalize_reset();
+        let mut output = Vec::with_capacity(out_len.next_multiple_of(32));
+        let mut counter: u32 = 1;
+        while output.len() < out_len {
+            let mut block = Sha256::new();
+            Digest::update(&mut block, counter.to_be_bytes());
+            Digest::update(&mut block, seed);
+            output.extend_from_slice(&block.finalize());
+            counter += 1;
+        }
+        output.truncate(out_len);
+        output
+    }
+}
+
+/// SHAKE256 conditioning function. The XOF produces any output length
+/// directly, without the counter construction `Sha256Conditioner` needs.
+/// Extracting consumes the input, as with `Sha256Conditioner`.
+#[derive(Debug, Clone, Default)]
+pub struct Shake256Conditioner {
+    hasher: Shake256,
+}
+
+impl Shake256Conditioner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn absorb(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    pub fn extract(&mut self, out_len: usize) -> Vec<u8> {
+        let mut output = vec![0u8; out_len];
+        self.hasher.finalize_xof_reset().read(&mut output);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+#[cfg(test)]
+use super::*;
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+}
+
+#[test]
+fn test_sha256_known_answer() {
+    // SHA-256(be32(1) || SHA-256("abc")) followed by the counter-2 block
+    let mut conditioner = Sha256Conditioner::new();
+    conditioner.absorb(b"abc");
+    assert_eq!(
+        conditioner.extract(40),
+        hex("368f51784bf50c00e7240145078e9ce402068943704c59acddbb2b276bf24553efa15e8a0122642b"),
+    );
+}
+
+#[test]
+fn test_shake256_known_answer() {
+    let mut conditioner = Shake256Conditioner::new();
+    conditioner.absorb(b"abc");
+    assert_eq!(
+        conditioner.extract(40),
+        hex("483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739d5a15bef186a5386"),
+    );
+
+    // Extracting resets to the empty input
+    assert_eq!(
+        conditioner.extract(32),
+        hex("46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f"),
+    );
+}
+
+#[test]
+fn test_extract_is_deterministic() {
+    let input: Vec<u8> = (0..=255u8).collect();
+
+    let mut first = Sha256Conditioner::new();
+    first.absorb(&input);
+    let mut second = Sha256Conditioner::new();
+    second.absorb(&input[..100]);
+    second.absorb(&input[100..]);
+    assert_eq!(first.extract(64), second.extract(64));
+
+    // Different input gives different output
+    let mut other = Sha256Conditioner::new();
+    other.absorb(&input[1..]);
+    let mut again = Sha256Conditioner::new();
+    again.absorb(&input);
+    assert_ne!(other.extract(64), again.extract(64));
+}
+
+#[test]
+fn test_extract_length() {
+    for len in [0, 1, 31, 32, 33, 100, 1024] {
+        let mut conditioner = Sha256Conditioner::new();
+        conditioner.absorb(b"raw entropy");
+        assert_eq!(conditioner.extract(len).len(), len);
+    }
+}
+
+#[test]
+fn test_extract_consumes_input() {
+    let mut conditioner = Sha256Conditioner::new();
+    conditioner.absorb(b"raw entropy");
+    let first = conditioner.extract(32);
+    let second = conditioner.extract(32);
+    assert_ne!(first, second);
+
+    // Longer output is a prefix-compatible stretch of the same seed
+    let mut short = Sha256Conditioner::new();
+    short.absorb(b"raw entropy");
+    let mut long = Sha256Conditioner::new();
+    long.absorb(b"raw entropy");
+    assert_eq!(short.extract(16), long.extract(80)[..16]);
+}
+
+#[test]
+fn test_von_neumann_constant_input_yields_nothing() {
+    for byte in [0x00, 0xff] {
+        assert!(von_neumann(&[byte; 1024]).is_empty());
+    }
+}
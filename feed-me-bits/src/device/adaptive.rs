@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+use futures::Stream;
+use crate::error::QrngError;
+use super::QrngDevice;
+
+/// Bounds and thresholds for `QrngDevice::adaptive_entropy_stream`.
+///
+/// A consumer that polls again almost immediately is waiting on the device,
+/// so chunks double to cut per-transfer overhead. One that lingers is
+/// falling behind, so chunks halve to keep each delivery small and timely.
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveChunking {
+    pub min_chunk: usize,
+    pub max_chunk: usize,
+    pub initial_chunk: usize,
+    /// Grow when the consumer polls again within this long of the last chunk.
+    pub grow_below: Duration,
+    /// Shrink when the consumer takes longer than this to poll again.
+    pub shrink_above: Duration,
+}
+
+impl Default for AdaptiveChunking {
+    fn default() -> Self {
+        Self {
+            min_chunk: 64,
+            max_chunk: 64 * 1024,
+            initial_chunk: 1024,
+            grow_below: Duration::from_millis(1),
+            shrink_above: Duration::from_millis(10),
+        }
+    }
+}
+
+struct AdaptiveState {
+    device: QrngDevice,
+    chunking: AdaptiveChunking,
+    chunk_size: usize,
+    last_yield: Option<Instant>,
+}
+
+impl AdaptiveState {
+    fn adapt(&mut self) {
+        let Some(last_yield) = self.last_yield else {
+            return;
+        };
+        let idle = last_yield.elapsed();
+        if idle < self.chunking.grow_below {
+            self.chunk_size = (self.chunk_size * 2).min(self.chunking.max_chunk);
+        } else if idle > self.chunking.shrink_above {
+            self.chunk_size = (self.chunk_size / 2).max(self.chunking.min_chunk);
+        }
+    }
+}
+
+impl QrngDevice {
+    /// Like `entropy_stream`, but the chunk size follows how quickly the
+    /// consumer drains the stream, within the bounds of `chunking`.
+    pub fn adaptive_entropy_stream(&self, chunking: AdaptiveChunking) -> Result<impl Stream<Item = Result<Vec<u8>, QrngError>>, QrngError> {
+        if chunking.min_chunk == 0 || chunking.min_chunk > chunking.max_chunk {
+            return Err(QrngError::InvalidState("Invalid chunk size bounds".to_string()));
+        }
+
+        let state = AdaptiveState {
+            device: self.clone(),
+            chunking,
+            chunk_size: chunking.initial_chunk.clamp(chunking.min_chunk, chunking.max_chunk),
+            last_yield: None,
+        };
+        Ok(futures::stream::unfold(Some(state), |state| async move {
+            let mut state = state?;
+            state.adapt();
+            match state.device.read_entropy(state.chunk_size).await {
+                Ok(chunk) => {
+                    state.last_yield = Some(Instant::now());
+                    Some((Ok(chunk), Some(state)))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        }))
+    }
+}
@@ -0,0 +1,31 @@
+use std::time::{Duration, Instant};
+use crate::error::QrngError;
+use super::QrngDevice;
+
+/// Result of `QrngDevice::benchmark`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ThroughputReport {
+    /// Bytes delivered by the device.
+    pub bytes: u64,
+    /// Time spent reading, at least the requested duration.
+    pub elapsed: Duration,
+    pub bytes_per_sec: f64,
+}
+
+impl QrngDevice {
+    /// Read for `duration` and report the device's byte rate. Reads
+    /// `max_transfer` bytes at a time into one reused buffer, so the figure
+    /// reflects the USB path rather than allocation. A failed read ends the
+    /// benchmark with its error.
+    pub async fn benchmark(&self, duration: Duration) -> Result<ThroughputReport, QrngError> {
+        let mut buffer = vec![0u8; self.config.max_transfer];
+        let mut bytes = 0u64;
+        let started = Instant::now();
+        while started.elapsed() < duration {
+            bytes += self.read_entropy_into(&mut buffer).await? as u64;
+        }
+        let elapsed = started.elapsed();
+        Ok(ThroughputReport { bytes, elapsed, bytes_per_sec: bytes as f64 / elapsed.as_secs_f64() })
+    }
+}
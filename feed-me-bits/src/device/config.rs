@@ -0,0 +1,49 @@
+use std::time::Duration;
+use super::{DEFAULT_ENTROPY_ENDPOINT, DEFAULT_MAX_TRANSFER, DEFAULT_READ_TIMEOUT, DEFAULT_STATUS_ENDPOINT};
+
+/// Configuration value selected by the default init sequence.
+pub const DEFAULT_CONFIG_VALUE: u8 = 1;
+
+/// Interface claimed by the default init sequence.
+pub const DEFAULT_INTERFACE: u8 = 0;
+
+/// USB layout of a QRNG, for firmware revisions that differ from the FTDI
+/// defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceConfig {
+    /// Configuration selected by the default init sequence.
+    pub config_value: u8,
+    /// Interface claimed on initialize, released on close and relinquish.
+    pub interface: u8,
+    pub entropy_endpoint: u8,
+    pub status_endpoint: u8,
+    /// Timeout applied to each bulk read.
+    pub read_timeout: Duration,
+    /// Longest single bulk transfer; larger reads are split.
+    pub max_transfer: usize,
+    /// Whether `status` checks the frame's trailing checksum byte. Turn off
+    /// for firmware that doesn't send one.
+    pub verify_checksum: bool,
+    /// FTDI latency timer set by `initialize`, in milliseconds. `None`
+    /// leaves the chip's own, usually 16 ms; 1 suits small polled reads.
+    pub latency_timer: Option<u8>,
+    /// `(mask, mode)` passed to `set_bitmode` by `initialize`. `None`
+    /// leaves the chip's mode alone.
+    pub bitmode: Option<(u8, u8)>,
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        Self {
+            config_value: DEFAULT_CONFIG_VALUE,
+            interface: DEFAULT_INTERFACE,
+            entropy_endpoint: DEFAULT_ENTROPY_ENDPOINT,
+            status_endpoint: DEFAULT_STATUS_ENDPOINT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            max_transfer: DEFAULT_MAX_TRANSFER,
+            verify_checksum: true,
+            latency_timer: None,
+            bitmode: None,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+use tracing::{info, warn};
+use crate::error::QrngError;
+use super::{DeviceManager, QrngDevice};
+
+/// How many windows to wait for another process to give the interface back.
+pub const RECLAIM_ATTEMPTS: usize = 3;
+
+impl QrngDevice {
+    /// Release the device's interface for `window` so another process can claim the
+    /// device, then claim it back. Reads from this process wait until the
+    /// interface is reclaimed. While the other process still holds it,
+    /// reclaiming is retried after another window, up to `RECLAIM_ATTEMPTS`
+    /// times.
+    pub async fn relinquish(&self, window: Duration) -> Result<(), QrngError> {
+        if !self.initialized {
+            return Err(QrngError::DeviceNotInitialized);
+        }
+
+        let mut transport = self.transport.lock().await;
+        transport.release_interface(self.config.interface)?;
+        info!("Released interface for {:?}", window);
+
+        let mut attempt = 1;
+        loop {
+            tokio::time::sleep(window).await;
+            match transport.claim_interface(self.config.interface) {
+                Err(QrngError::UsbError(rusb::Error::Busy)) if attempt < RECLAIM_ATTEMPTS => {
+                    warn!("Interface still busy, waiting another {:?}", window);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl DeviceManager {
+    /// Cooperative mode for sharing devices with other processes. With a
+    /// window set, `relinquish` is available and `initialize_device` waits
+    /// out a busy interface instead of failing. Applies to this handle and
+    /// clones made after the call.
+    pub fn set_relinquish_window(&mut self, window: Option<Duration>) {
+        self.relinquish_window = window;
+    }
+
+    /// Hand `serial`'s interface to another process for the relinquish
+    /// window, then reclaim it.
+    pub async fn relinquish(&self, serial: &str) -> Result<(), QrngError> {
+        let window = self.relinquish_window
+            .ok_or_else(|| QrngError::InvalidState("Cooperative mode is not enabled".to_string()))?;
+        self.get_device(serial).await?.relinquish(window).await
+    }
+
+    // Initialize, retrying after the relinquish window while another process
+    // holds the interface
+    pub(crate) async fn initialize_cooperatively(&self, device: &mut QrngDevice) -> Result<(), QrngError> {
+        let Some(window) = self.relinquish_window else {
+            return device.initialize().await;
+        };
+
+        let mut attempt = 1;
+        loop {
+            match device.initialize().await {
+                Err(QrngError::UsbError(rusb::Error::Busy)) if attempt < RECLAIM_ATTEMPTS => {
+                    warn!("Device busy, retrying in {:?}", window);
+                    tokio::time::sleep(window).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// When to flag a device as degraded based on its recent read failures.
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorRateAlarm {
+    /// How far back reads count towards the rate.
+    pub window: Duration,
+    /// Error rate (0.0 to 1.0) at or above which the device is degraded.
+    pub threshold: f64,
+    /// Reads needed in the window before the rate is trusted.
+    pub min_reads: usize,
+}
+
+impl Default for ErrorRateAlarm {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            threshold: 0.1,
+            min_reads: 10,
+        }
+    }
+}
+
+/// Rolling record of read outcomes for one device.
+#[derive(Debug, Default)]
+pub(crate) struct ErrorRate {
+    reads: VecDeque<(Instant, bool)>,
+    degraded: bool,
+}
+
+impl ErrorRate {
+    /// Record a read outcome. Returns the new degraded state if it changed.
+    pub(crate) fn record(&mut self, alarm: &ErrorRateAlarm, now: Instant, failed: bool) -> Option<bool> {
+        self.reads.push_back((now, failed));
+        self.expire(alarm, now);
+
+        let degraded = self.reads.len() >= alarm.min_reads && self.rate() >= alarm.threshold;
+        if degraded != self.degraded {
+            self.degraded = degraded;
+            return Some(degraded);
+        }
+        None
+    }
+
+    pub(crate) fn rate_at(&mut self, alarm: &ErrorRateAlarm, now: Instant) -> f64 {
+        self.expire(alarm, now);
+        self.rate()
+    }
+
+    pub(crate) fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    fn expire(&mut self, alarm: &ErrorRateAlarm, now: Instant) {
+        while let Some(&(at, _)) = self.reads.front() {
+            if now.duration_since(at) <= alarm.window {
+                break;
+            }
+            self.reads.pop_front();
+        }
+    }
+
+    fn rate(&self) -> f64 {
+        if self.reads.is_empty() {
+            return 0.0;
+        }
+        let failures = self.reads.iter().filter(|(_, failed)| *failed).count();
+        failures as f64 / self.reads.len() as f64
+    }
+}
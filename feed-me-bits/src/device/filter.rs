@@ -0,0 +1,61 @@
+use rusb::DeviceDescriptor;
+use tracing::warn;
+use crate::{FTDI_VENDOR_ID, FTDI_PRODUCT_ID};
+use super::QrngDevice;
+
+/// Which USB devices `scan_devices_filtered` treats as QRNGs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceFilter {
+    pub vendor_ids: Vec<u16>,
+    /// Accept every product under `vendor_ids` when empty.
+    pub product_ids: Vec<u16>,
+    /// Only keep devices whose serial is listed. Devices whose serial can't
+    /// be read are skipped when set.
+    pub serial_allowlist: Option<Vec<String>>,
+}
+
+impl Default for DeviceFilter {
+    /// The FTDI QRNG matched by `scan_devices`.
+    fn default() -> Self {
+        Self {
+            vendor_ids: vec![FTDI_VENDOR_ID],
+            product_ids: vec![FTDI_PRODUCT_ID],
+            serial_allowlist: None,
+        }
+    }
+}
+
+impl DeviceFilter {
+    /// FTDI devices with any of `product_ids`, for QRNG models other than
+    /// the default one.
+    pub fn with_product_ids(product_ids: &[u16]) -> Self {
+        Self { product_ids: product_ids.to_vec(), ..Self::default() }
+    }
+
+    pub fn matches(&self, descriptor: &DeviceDescriptor) -> bool {
+        self.matches_ids(descriptor.vendor_id(), descriptor.product_id())
+    }
+
+    /// Whether a device with these descriptor IDs passes the filter, before
+    /// its serial is known.
+    pub fn matches_ids(&self, vendor_id: u16, product_id: u16) -> bool {
+        self.vendor_ids.contains(&vendor_id)
+            && (self.product_ids.is_empty() || self.product_ids.contains(&product_id))
+    }
+
+    /// Drop devices missing from the serial allowlist, if there is one.
+    pub(crate) async fn retain_allowed(&self, devices: Vec<QrngDevice>) -> Vec<QrngDevice> {
+        let Some(allowlist) = &self.serial_allowlist else {
+            return devices;
+        };
+        let mut allowed = Vec::with_capacity(devices.len());
+        for device in devices {
+            match device.serial().await {
+                Ok(serial) if allowlist.contains(&serial) => allowed.push(device),
+                Ok(_) => {}
+                Err(e) => warn!("Skipping device with unreadable serial: {}", e),
+            }
+        }
+        allowed
+    }
+}
@@ -0,0 +1,45 @@
+use crate::error::QrngError;
+use super::QrngDevice;
+
+/// A vendor control request that returns the firmware revision as ASCII,
+/// for devices that report more detail than `bcdDevice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirmwareQuery {
+    pub request_type: u8,
+    pub request: u8,
+    pub value: u16,
+    pub index: u16,
+    /// Longest reply to accept.
+    pub length: usize,
+}
+
+impl QrngDevice {
+    /// The device's firmware revision. Without a `query` this is the
+    /// descriptor's `bcdDevice` as `major.minor`, e.g. `"6.0"`. With one,
+    /// the revision string is read from the device by that control request,
+    /// which needs the device initialized.
+    pub async fn firmware_version(&self, query: Option<&FirmwareQuery>) -> Result<String, QrngError> {
+        let Some(query) = query else {
+            let (major, minor) = self.device_version;
+            return Ok(format!("{}.{}", major, minor));
+        };
+
+        let mut buffer = vec![0u8; query.length];
+        let n = self.transport.lock().await.read_control(
+            query.request_type,
+            query.request,
+            query.value,
+            query.index,
+            &mut buffer,
+            self.config.read_timeout,
+        )?;
+        let version = std::str::from_utf8(&buffer[..n])
+            .map_err(|_| QrngError::ProtocolError("Firmware version is not ASCII".to_string()))?
+            .trim_end_matches('\0')
+            .trim();
+        if version.is_empty() {
+            return Err(QrngError::ProtocolError("Empty firmware version".to_string()));
+        }
+        Ok(version.to_string())
+    }
+}
@@ -0,0 +1,138 @@
+use crate::error::QrngError;
+use super::transport::Transport;
+use super::QrngDevice;
+
+/// `bmRequestType` of FTDI vendor requests: host-to-device, vendor, device
+/// recipient.
+pub const FTDI_OUT_REQUEST_TYPE: u8 = 0x40;
+
+/// FTDI `SIO_RESET` vendor request. Its `wValue` selects what is reset.
+pub const SIO_RESET_REQUEST: u8 = 0x00;
+
+/// `SIO_RESET` value that discards the chip's receive FIFO, i.e. bytes on
+/// their way from the device to the host.
+pub const SIO_RESET_PURGE_RX: u16 = 1;
+
+/// `SIO_RESET` value that discards the chip's transmit FIFO.
+pub const SIO_RESET_PURGE_TX: u16 = 2;
+
+/// FTDI request setting how many milliseconds the chip waits before
+/// sending a partly filled packet. `wValue` is the timer.
+pub const SIO_SET_LATENCY_TIMER_REQUEST: u8 = 0x09;
+
+/// FTDI request selecting the bit mode. `wValue` carries the mode in its
+/// high byte and the pin direction mask in its low byte.
+pub const SIO_SET_BITMODE_REQUEST: u8 = 0x0b;
+
+/// Bulk IN endpoint of channel `interface` on FTDI chips: `0x81` for the
+/// first channel, `0x83` for the second and so on.
+pub const fn ftdi_in_endpoint(interface: u8) -> u8 {
+    0x81 + 2 * interface
+}
+
+fn latency_timer_value(ms: u8) -> Result<u16, QrngError> {
+    if ms == 0 {
+        return Err(QrngError::InvalidState("Latency timer must be 1-255 ms".to_string()));
+    }
+    Ok(u16::from(ms))
+}
+
+/// FTDI chip and its channel count by the major version of `bcdDevice`,
+/// as libftdi identifies them. `rusb` decodes the BCD, so bcdDevice 0x1000
+/// is major 10.
+fn ftdi_chip(major: u8) -> Option<(&'static str, u8)> {
+    match major {
+        2 => Some(("FT232AM", 1)),
+        4 => Some(("FT232BM", 1)),
+        5 => Some(("FT2232C", 2)),
+        6 => Some(("FT232R", 1)),
+        7 => Some(("FT2232H", 2)),
+        8 => Some(("FT4232H", 4)),
+        9 => Some(("FT232H", 1)),
+        10 => Some(("FT230X", 1)),
+        _ => None,
+    }
+}
+
+impl QrngDevice {
+    /// The FTDI chip, e.g. `"FT232R"`, going by the descriptor's
+    /// `bcdDevice`. `None` for a revision this crate doesn't recognise.
+    pub fn chip(&self) -> Option<&'static str> {
+        ftdi_chip(self.device_version.0).map(|(chip, _)| chip)
+    }
+
+    /// Channels on the chip, each claimable with `with_interface`. 1 when
+    /// the chip isn't recognised.
+    pub fn channels(&self) -> u8 {
+        ftdi_chip(self.device_version.0).map_or(1, |(_, channels)| channels)
+    }
+
+    /// Discard whatever is waiting in the FTDI receive and transmit FIFOs,
+    /// such as stale bytes left behind by a previous session.
+    pub async fn flush(&self) -> Result<(), QrngError> {
+        self.vendor_requests(SIO_RESET_REQUEST, &[SIO_RESET_PURGE_RX, SIO_RESET_PURGE_TX]).await
+    }
+
+    // `flush` with the transport lock already held
+    pub(crate) fn flush_locked(&self, transport: &mut dyn Transport) -> Result<(), QrngError> {
+        self.vendor_requests_locked(transport, SIO_RESET_REQUEST, &[SIO_RESET_PURGE_RX, SIO_RESET_PURGE_TX])
+    }
+
+    /// Set the chip's latency timer to `ms`, between 1 and 255. Lower
+    /// values cut the wait for short reads at the cost of more, smaller
+    /// packets.
+    pub async fn set_latency_timer(&self, ms: u8) -> Result<(), QrngError> {
+        self.vendor_requests(SIO_SET_LATENCY_TIMER_REQUEST, &[latency_timer_value(ms)?]).await
+    }
+
+    /// Select bit mode `mode` with pin direction `mask`, as in libftdi's
+    /// `ftdi_set_bitmode`.
+    pub async fn set_bitmode(&self, mask: u8, mode: u8) -> Result<(), QrngError> {
+        self.vendor_requests(SIO_SET_BITMODE_REQUEST, &[u16::from_be_bytes([mode, mask])]).await
+    }
+
+    // Apply the configured latency timer and bit mode
+    pub(crate) async fn configure_ftdi(&self) -> Result<(), QrngError> {
+        let mut transport = self.transport.lock().await;
+        self.configure_ftdi_locked(transport.as_mut())
+    }
+
+    // `configure_ftdi` with the transport lock already held, e.g. while
+    // reopening the device
+    pub(crate) fn configure_ftdi_locked(&self, transport: &mut dyn Transport) -> Result<(), QrngError> {
+        if let Some(ms) = self.config.latency_timer {
+            self.vendor_requests_locked(transport, SIO_SET_LATENCY_TIMER_REQUEST, &[latency_timer_value(ms)?])?;
+        }
+        if let Some((mask, mode)) = self.config.bitmode {
+            self.vendor_requests_locked(transport, SIO_SET_BITMODE_REQUEST, &[u16::from_be_bytes([mode, mask])])?;
+        }
+        Ok(())
+    }
+
+    /// Issue `request` once per value, in order, to the claimed interface.
+    /// A request the chip refuses is a `CommunicationError`.
+    async fn vendor_requests(&self, request: u8, values: &[u16]) -> Result<(), QrngError> {
+        let mut transport = self.transport.lock().await;
+        self.vendor_requests_locked(transport.as_mut(), request, values)
+    }
+
+    fn vendor_requests_locked(&self, transport: &mut dyn Transport, request: u8, values: &[u16]) -> Result<(), QrngError> {
+        if !self.initialized {
+            return Err(QrngError::DeviceNotInitialized);
+        }
+
+        // FTDI numbers its ports from 1 in wIndex
+        let index = u16::from(self.config.interface) + 1;
+        for &value in values {
+            transport.write_control(FTDI_OUT_REQUEST_TYPE, request, value, index, &[], self.config.read_timeout)
+                .map_err(|e| match e {
+                    QrngError::UsbError(rusb::Error::NoDevice) => QrngError::DeviceDisconnected,
+                    QrngError::UsbError(e) => QrngError::CommunicationError(format!(
+                        "FTDI request {:#04x} with value {:#06x} failed: {}", request, value, e
+                    )),
+                    e => e,
+                })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use rusb::{Context, Device, HotplugBuilder, UsbContext};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use crate::error::QrngError;
+use crate::{FTDI_VENDOR_ID, FTDI_PRODUCT_ID};
+use super::transport::{Transport, UsbTransport};
+use super::{DeviceManager, QrngDevice};
+
+/// Bus number and address, the only way to identify a device once it has left.
+pub(crate) type BusLocation = (u8, u8);
+
+// How often the event pump checks whether it has been asked to stop
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub(crate) enum HotplugEvent {
+    Arrived { location: BusLocation, transport: Box<dyn Transport> },
+    Left { location: BusLocation },
+}
+
+/// Forwards libusb hotplug callbacks to the async side. Descriptor strings
+/// can't be read from inside a callback, so the `QrngDevice`, which reads
+/// its serial when built, is built later.
+struct HotplugForwarder {
+    events: mpsc::UnboundedSender<HotplugEvent>,
+}
+
+impl rusb::Hotplug<Context> for HotplugForwarder {
+    fn device_arrived(&mut self, device: Device<Context>) {
+        let location = (device.bus_number(), device.address());
+        match device.device_descriptor() {
+            Ok(descriptor) => {
+                let transport = Box::new(UsbTransport::new(device, descriptor));
+                let _ = self.events.send(HotplugEvent::Arrived { location, transport });
+            }
+            Err(e) => warn!("Failed to read descriptor of hotplugged device: {}", e),
+        }
+    }
+
+    fn device_left(&mut self, device: Device<Context>) {
+        let location = (device.bus_number(), device.address());
+        let _ = self.events.send(HotplugEvent::Left { location });
+    }
+}
+
+/// A running hotplug watch, held by the `DeviceManager` until stopped.
+pub(crate) struct HotplugWatch {
+    stop: Arc<AtomicBool>,
+    events_task: tokio::task::JoinHandle<()>,
+}
+
+impl DeviceManager {
+    /// Keep the manager in sync with QRNG devices plugged in or removed after
+    /// startup. Devices present before the call are not reported; use
+    /// `scan_devices` for those. Only one watch runs per manager.
+    pub fn start_hotplug_watch(&self) -> Result<(), QrngError> {
+        if !rusb::has_hotplug() {
+            return Err(QrngError::InvalidState("USB hotplug is not supported on this platform".to_string()));
+        }
+
+        let mut watch = self.hotplug.lock().unwrap();
+        if watch.is_some() {
+            return Err(QrngError::InvalidState("Hotplug watch already running".to_string()));
+        }
+
+        let context = Context::new()?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        let registration = HotplugBuilder::new()
+            .vendor_id(FTDI_VENDOR_ID)
+            .product_id(FTDI_PRODUCT_ID)
+            .register(&context, Box::new(HotplugForwarder { events: tx }))?;
+
+        // libusb only delivers callbacks from inside handle_events. Dropping
+        // the registration on exit closes the channel and ends the async side.
+        let stop = Arc::new(AtomicBool::new(false));
+        let pump_stop = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            let _registration = registration;
+            while !pump_stop.load(Ordering::Relaxed) {
+                if let Err(e) = context.handle_events(Some(STOP_POLL_INTERVAL)) {
+                    warn!("Stopping hotplug watch: {}", e);
+                    break;
+                }
+            }
+        });
+
+        let events_task = tokio::spawn(self.clone().apply_hotplug_events(rx));
+        *watch = Some(HotplugWatch { stop, events_task });
+        info!("Watching for QRNG hotplug events");
+        Ok(())
+    }
+
+    /// Stop a watch started with `start_hotplug_watch`. Devices already in the
+    /// manager are left in place.
+    pub fn stop_hotplug_watch(&self) -> Result<(), QrngError> {
+        let watch = self.hotplug.lock().unwrap().take()
+            .ok_or_else(|| QrngError::InvalidState("Hotplug watch is not running".to_string()))?;
+        watch.stop.store(true, Ordering::Relaxed);
+        watch.events_task.abort();
+        info!("Stopped watching for QRNG hotplug events");
+        Ok(())
+    }
+
+    pub(crate) async fn apply_hotplug_events(self, mut events: mpsc::UnboundedReceiver<HotplugEvent>) {
+        let mut serials: HashMap<BusLocation, String> = HashMap::new();
+        while let Some(event) = events.recv().await {
+            match event {
+                HotplugEvent::Arrived { location, transport } => {
+                    let device = match tokio::task::spawn_blocking(|| QrngDevice::from_transport(transport)).await {
+                        Ok(device) => device,
+                        Err(e) => {
+                            warn!("Failed to open hotplugged device: {}", e);
+                            continue;
+                        }
+                    };
+                    match self.add_device(device).await {
+                        Ok(serial) => {
+                            info!("QRNG device {} attached", serial);
+                            serials.insert(location, serial);
+                        }
+                        Err(e) => warn!("Failed to add hotplugged device: {}", e),
+                    }
+                }
+                HotplugEvent::Left { location } => {
+                    if let Some(serial) = serials.remove(&location) {
+                        if self.remove_device(&serial).await.is_ok() {
+                            info!("QRNG device {} detached", serial);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
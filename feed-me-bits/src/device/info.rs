@@ -0,0 +1,62 @@
+use crate::error::QrngError;
+use super::QrngDevice;
+
+/// Identity of a device, as reported by its USB descriptors, and whether
+/// it has been initialized.
+///
+/// Serialized with the vendor and product IDs as hex strings, e.g.
+/// `"0x0403"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceInfo {
+    pub serial: String,
+    #[cfg_attr(feature = "serde", serde(with = "hex_id"))]
+    pub vendor_id: u16,
+    #[cfg_attr(feature = "serde", serde(with = "hex_id"))]
+    pub product_id: u16,
+    /// `None` if the descriptor string could not be read.
+    pub manufacturer: Option<String>,
+    pub description: Option<String>,
+    /// USB specification release the device claims, e.g. `"2.0"`.
+    pub usb_version: String,
+    pub bus_number: u8,
+    pub address: u8,
+    pub initialized: bool,
+}
+
+impl QrngDevice {
+    /// Read the descriptor strings into a `DeviceInfo`, each once and under
+    /// a single lock of the transport. Only an unreadable serial is an
+    /// error; the other strings are left `None`.
+    pub async fn info(&self) -> Result<DeviceInfo, QrngError> {
+        let mut transport = self.transport.lock().await;
+        let (major, minor) = self.usb_version;
+        Ok(DeviceInfo {
+            serial: transport.serial()?,
+            vendor_id: self.vendor_id,
+            product_id: self.product_id,
+            manufacturer: transport.manufacturer().ok(),
+            description: transport.description().ok(),
+            usb_version: format!("{}.{}", major, minor),
+            bus_number: self.bus_number,
+            address: self.address,
+            initialized: self.initialized,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+mod hex_id {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#06x}", id))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        let digits = hex.strip_prefix("0x").unwrap_or(&hex);
+        u16::from_str_radix(digits, 16).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt::Debug;
+use std::time::Duration;
+use rusb::{Context, DeviceHandle};
+use crate::error::QrngError;
+use super::{DEFAULT_CONFIG_VALUE, DEFAULT_INTERFACE};
+
+/// The subset of USB handle operations available to an `InitSequence`.
+pub trait UsbHandle {
+    fn reset(&mut self) -> Result<(), QrngError>;
+    fn set_active_configuration(&mut self, config: u8) -> Result<(), QrngError>;
+    fn claim_interface(&mut self, iface: u8) -> Result<(), QrngError>;
+    fn write_control(&mut self, request_type: u8, request: u8, value: u16, index: u16, buf: &[u8], timeout: Duration) -> Result<usize, QrngError>;
+    fn write_bulk(&mut self, endpoint: u8, buf: &[u8], timeout: Duration) -> Result<usize, QrngError>;
+    fn read_bulk(&mut self, endpoint: u8, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError>;
+}
+
+impl UsbHandle for DeviceHandle<Context> {
+    fn reset(&mut self) -> Result<(), QrngError> {
+        Ok(DeviceHandle::reset(self)?)
+    }
+
+    fn set_active_configuration(&mut self, config: u8) -> Result<(), QrngError> {
+        Ok(DeviceHandle::set_active_configuration(self, config)?)
+    }
+
+    fn claim_interface(&mut self, iface: u8) -> Result<(), QrngError> {
+        Ok(DeviceHandle::claim_interface(self, iface)?)
+    }
+
+    fn write_control(&mut self, request_type: u8, request: u8, value: u16, index: u16, buf: &[u8], timeout: Duration) -> Result<usize, QrngError> {
+        Ok(DeviceHandle::write_control(self, request_type, request, value, index, buf, timeout)?)
+    }
+
+    fn write_bulk(&mut self, endpoint: u8, buf: &[u8], timeout: Duration) -> Result<usize, QrngError> {
+        Ok(DeviceHandle::write_bulk(self, endpoint, buf, timeout)?)
+    }
+
+    fn read_bulk(&mut self, endpoint: u8, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError> {
+        Ok(DeviceHandle::read_bulk(self, endpoint, buf, timeout)?)
+    }
+}
+
+/// Commands issued to a freshly opened device before it serves entropy.
+///
+/// Firmwares differ in what they need here; set a custom sequence with
+/// `QrngDevice::set_init_sequence`. Runs while the device lock is held, so
+/// implementations should stick to the USB calls on `handle`.
+pub trait InitSequence: Send + Sync + Debug {
+    fn init(&self, handle: &mut dyn UsbHandle) -> Result<(), QrngError>;
+}
+
+/// Init sequence for the FTDI MED QRNG: reset, select `configuration` and
+/// claim `interface` (1 and 0 by default).
+#[derive(Debug, Clone, Copy)]
+pub struct FtdiInitSequence {
+    pub configuration: u8,
+    pub interface: u8,
+}
+
+impl Default for FtdiInitSequence {
+    fn default() -> Self {
+        Self {
+            configuration: DEFAULT_CONFIG_VALUE,
+            interface: DEFAULT_INTERFACE,
+        }
+    }
+}
+
+impl InitSequence for FtdiInitSequence {
+    fn init(&self, handle: &mut dyn UsbHandle) -> Result<(), QrngError> {
+        // Reset device
+        handle.reset()?;
+
+        // Set configuration
+        handle.set_active_configuration(self.configuration)?;
+
+        // Claim interface
+        handle.claim_interface(self.interface)?;
+        Ok(())
+    }
+}
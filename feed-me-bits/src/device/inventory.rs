@@ -0,0 +1,114 @@
+use super::{DeviceConfig, DeviceManager};
+
+/// Every device a `DeviceManager` knows about, in serial order.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Inventory {
+    pub devices: Vec<InventoryEntry>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct InventoryEntry {
+    /// The device's key in the manager.
+    pub serial: String,
+    /// `None` if the descriptor string could not be read.
+    pub manufacturer: Option<String>,
+    /// The product string, the device's human-readable label.
+    pub description: Option<String>,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// `QrngDevice::firmware_version` without a query.
+    pub firmware: Option<String>,
+    /// `QrngDevice::chip`.
+    pub chip: Option<&'static str>,
+    /// `QrngDevice::channels`.
+    pub channels: u8,
+    pub initialized: bool,
+    pub health: DeviceHealth,
+    pub error_rate: f64,
+    pub config: DeviceConfigSummary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "snake_case"))]
+pub enum DeviceHealth {
+    Healthy,
+    /// Error rate is over the manager's `ErrorRateAlarm` threshold.
+    Degraded,
+    /// Set aside by `DisconnectStrategy::Quarantine`; not served.
+    Quarantined,
+}
+
+/// A device's `DeviceConfig`, plus whether reads are health tested.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DeviceConfigSummary {
+    pub config_value: u8,
+    pub interface: u8,
+    pub entropy_endpoint: u8,
+    pub status_endpoint: u8,
+    pub read_timeout_ms: u64,
+    pub max_transfer: usize,
+    pub verify_checksum: bool,
+    pub latency_timer: Option<u8>,
+    /// `(mask, mode)`.
+    pub bitmode: Option<(u8, u8)>,
+    pub health_tests: bool,
+}
+
+impl DeviceConfigSummary {
+    fn new(config: DeviceConfig, health_tests: bool) -> Self {
+        Self {
+            config_value: config.config_value,
+            interface: config.interface,
+            entropy_endpoint: config.entropy_endpoint,
+            status_endpoint: config.status_endpoint,
+            read_timeout_ms: config.read_timeout.as_millis() as u64,
+            max_transfer: config.max_transfer,
+            verify_checksum: config.verify_checksum,
+            latency_timer: config.latency_timer,
+            bitmode: config.bitmode,
+            health_tests,
+        }
+    }
+}
+
+impl DeviceManager {
+    /// Snapshot of all active and quarantined devices for tooling.
+    pub async fn inventory(&self) -> Inventory {
+        let active: Vec<_> = self.devices.lock().await.clone().into_iter().collect();
+        let quarantined: Vec<_> = self.quarantine.lock().await.clone().into_iter().collect();
+
+        let mut devices = Vec::with_capacity(active.len() + quarantined.len());
+        for (serial, device, quarantined) in active.into_iter().map(|(s, d)| (s, d, false))
+            .chain(quarantined.into_iter().map(|(s, d)| (s, d, true)))
+        {
+            let health = if quarantined {
+                DeviceHealth::Quarantined
+            } else if self.is_degraded(&serial).await.unwrap_or(false) {
+                DeviceHealth::Degraded
+            } else {
+                DeviceHealth::Healthy
+            };
+            let error_rate = if quarantined { 0.0 } else { self.error_rate(&serial).await.unwrap_or(0.0) };
+
+            devices.push(InventoryEntry {
+                manufacturer: device.manufacturer().await.ok(),
+                description: device.description().await.ok(),
+                vendor_id: device.vendor_id(),
+                product_id: device.product_id(),
+                firmware: device.firmware_version(None).await.ok(),
+                chip: device.chip(),
+                channels: device.channels(),
+                initialized: device.is_initialized(),
+                health,
+                error_rate,
+                config: DeviceConfigSummary::new(device.config(), device.health_monitor.is_some()),
+                serial,
+            });
+        }
+        devices.sort_by(|a, b| a.serial.cmp(&b.serial));
+        Inventory { devices }
+    }
+}
@@ -0,0 +1,393 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use super::transport::Transport;
+use super::init::{InitSequence, UsbHandle};
+use super::QrngDevice;
+use crate::error::QrngError;
+use crate::source::{BoxFuture, EntropySource};
+use crate::{FTDI_VENDOR_ID, FTDI_PRODUCT_ID};
+
+/// A USB command issued to the mock through `UsbHandle`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum UsbCommand {
+    Reset,
+    SetConfiguration(u8),
+    ClaimInterface(u8),
+    ReleaseInterface(u8),
+    Control { request_type: u8, request: u8, value: u16, index: u16, data: Vec<u8> },
+    BulkOut { endpoint: u8, data: Vec<u8> },
+    BulkIn { endpoint: u8, len: usize },
+    ControlIn { request_type: u8, request: u8, value: u16, index: u16, len: usize },
+}
+
+#[derive(Debug)]
+struct MockState {
+    script: VecDeque<rusb::Result<Vec<u8>>>,
+    seed: u64,
+    bulk_reads: usize,
+    responding: bool,
+    last_timeout: Option<Duration>,
+    last_endpoint: Option<u8>,
+    bulk_in_endpoints: HashMap<u8, Vec<u8>>,
+    commands: Vec<UsbCommand>,
+    busy_claims: usize,
+    opens: usize,
+    open: bool,
+    interface: u8,
+    strings_readable: bool,
+    device_version: u16,
+    max_transfer: usize,
+    largest_read: usize,
+    control_stalls: bool,
+}
+
+impl MockState {
+    fn generate(&mut self, buf: &mut [u8]) {
+        for byte in buf.iter_mut() {
+            self.seed ^= self.seed << 13;
+            self.seed ^= self.seed >> 7;
+            self.seed ^= self.seed << 17;
+            *byte = self.seed as u8;
+        }
+    }
+}
+
+/// Scripted stand-in for a QRNG on the USB bus.
+///
+/// Bulk reads pop scripted results first; once the script is exhausted the
+/// mock fills the buffer from a deterministic xorshift stream. Clones share
+/// state so a test can keep a handle after the transport is moved into a
+/// `QrngDevice`.
+#[derive(Debug, Clone)]
+pub(crate) struct MockTransport {
+    serial: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    pub(crate) fn new(serial: &str) -> Self {
+        let seed = serial.bytes().fold(0x9e37_79b9_7f4a_7c15u64, |acc, b| {
+            (acc ^ b as u64).wrapping_mul(0x100_0000_01b3)
+        });
+        Self::with_seed(serial, seed)
+    }
+
+    /// Like `new`, but with the generated stream started from `seed`.
+    pub(crate) fn with_seed(serial: &str, seed: u64) -> Self {
+        Self {
+            serial: serial.to_string(),
+            state: Arc::new(Mutex::new(MockState {
+                script: VecDeque::new(),
+                seed: seed | 1,
+                bulk_reads: 0,
+                responding: true,
+                last_timeout: None,
+                last_endpoint: None,
+                bulk_in_endpoints: HashMap::new(),
+                commands: Vec::new(),
+                busy_claims: 0,
+                opens: 0,
+                open: false,
+                interface: 0,
+                strings_readable: true,
+                device_version: 0x0600,
+                max_transfer: usize::MAX,
+                largest_read: 0,
+                control_stalls: false,
+            })),
+        }
+    }
+
+    /// Queue the result of the next unscripted bulk read.
+    pub(crate) fn push_read(&self, result: rusb::Result<Vec<u8>>) {
+        self.state.lock().unwrap().script.push_back(result);
+    }
+
+    /// Make the manufacturer and description strings unreadable. The serial
+    /// always reads, since devices are keyed by it.
+    pub(crate) fn set_strings_readable(&self, readable: bool) {
+        self.state.lock().unwrap().strings_readable = readable;
+    }
+
+    fn string(&self, value: &str) -> Result<String, QrngError> {
+        if !self.state.lock().unwrap().strings_readable {
+            return Err(rusb::Error::Pipe.into());
+        }
+        Ok(value.to_string())
+    }
+
+    /// Set the raw BCD `bcdDevice` the descriptor reports.
+    pub(crate) fn set_device_version(&self, bcd: u16) {
+        self.state.lock().unwrap().device_version = bcd;
+    }
+
+    /// Number of bulk reads the mock has served, successful or not.
+    pub(crate) fn bulk_reads(&self) -> usize {
+        self.state.lock().unwrap().bulk_reads
+    }
+
+    /// Serve at most `max` bytes per bulk read, like a chip with a small
+    /// transfer buffer.
+    pub(crate) fn set_max_transfer(&self, max: usize) {
+        self.state.lock().unwrap().max_transfer = max;
+    }
+
+    /// Stall every control transfer, as a chip NAKing vendor requests.
+    pub(crate) fn set_control_stalls(&self, stalls: bool) {
+        self.state.lock().unwrap().control_stalls = stalls;
+    }
+
+    /// Longest buffer passed to a bulk read so far.
+    pub(crate) fn largest_read(&self) -> usize {
+        self.state.lock().unwrap().largest_read
+    }
+
+    /// A non-responding mock lets every bulk read run into its timeout.
+    pub(crate) fn set_responding(&self, responding: bool) {
+        self.state.lock().unwrap().responding = responding;
+    }
+
+    /// Timeout passed to the most recent bulk read.
+    pub(crate) fn last_timeout(&self) -> Option<Duration> {
+        self.state.lock().unwrap().last_timeout
+    }
+
+    /// Endpoint addressed by the most recent bulk read.
+    pub(crate) fn last_endpoint(&self) -> Option<u8> {
+        self.state.lock().unwrap().last_endpoint
+    }
+
+    /// Bulk IN endpoints of interface 0 reported by the configuration
+    /// descriptor. Empty by default, as if the descriptor could not be read.
+    pub(crate) fn set_bulk_in_endpoints(&self, endpoints: Vec<u8>) {
+        self.set_interface_endpoints(0, endpoints);
+    }
+
+    /// `set_bulk_in_endpoints` for another interface of a multi-channel chip.
+    pub(crate) fn set_interface_endpoints(&self, interface: u8, endpoints: Vec<u8>) {
+        self.state.lock().unwrap().bulk_in_endpoints.insert(interface, endpoints);
+    }
+
+    /// Commands issued by init sequences, in order.
+    pub(crate) fn commands(&self) -> Vec<UsbCommand> {
+        self.state.lock().unwrap().commands.clone()
+    }
+
+    /// Number of times the device has been opened by `initialize`.
+    pub(crate) fn opens(&self) -> usize {
+        self.state.lock().unwrap().opens
+    }
+
+    /// Whether the device is open, i.e. initialized and not yet closed.
+    pub(crate) fn is_open(&self) -> bool {
+        self.state.lock().unwrap().open
+    }
+
+    /// Fail the next `count` interface claims with `Busy`, as if another
+    /// process held the interface.
+    pub(crate) fn set_busy_claims(&self, count: usize) {
+        self.state.lock().unwrap().busy_claims = count;
+    }
+
+    fn record(&self, command: UsbCommand) {
+        self.state.lock().unwrap().commands.push(command);
+    }
+
+    /// A device over this transport. The startup check is off so scripted
+    /// reads and read counts are left to the test; enable it explicitly to
+    /// test it.
+    pub(crate) fn device(&self) -> QrngDevice {
+        let mut device = QrngDevice::from_transport(Box::new(self.clone()));
+        device.set_startup_check(false);
+        device
+    }
+}
+
+impl Transport for MockTransport {
+    fn vendor_id(&self) -> u16 {
+        FTDI_VENDOR_ID
+    }
+
+    fn product_id(&self) -> u16 {
+        FTDI_PRODUCT_ID
+    }
+
+    fn usb_version(&self) -> (u8, u8) {
+        (2, 0)
+    }
+
+    fn device_version(&self) -> (u8, u8) {
+        let version = rusb::Version::from_bcd(self.state.lock().unwrap().device_version);
+        (version.major(), version.minor())
+    }
+
+    fn bus_number(&self) -> u8 {
+        1
+    }
+
+    fn address(&self) -> u8 {
+        4
+    }
+
+    fn initialize(&mut self, sequence: &dyn InitSequence, interface: u8) -> Result<(), QrngError> {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.opens += 1;
+            state.open = true;
+            state.interface = interface;
+        }
+        let result = sequence.init(self);
+        self.state.lock().unwrap().open = result.is_ok();
+        result
+    }
+
+    fn read_bulk(&mut self, endpoint: u8, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError> {
+        let mut state = self.state.lock().unwrap();
+        if !state.open {
+            return Err(QrngError::DeviceNotInitialized);
+        }
+        state.bulk_reads += 1;
+        state.largest_read = state.largest_read.max(buf.len());
+        state.last_timeout = Some(timeout);
+        state.last_endpoint = Some(endpoint);
+        if !state.responding {
+            std::thread::sleep(timeout);
+            return Err(rusb::Error::Timeout.into());
+        }
+        if let Some(result) = state.script.pop_front() {
+            let data = result?;
+            let n = data.len().min(buf.len());
+            buf[..n].copy_from_slice(&data[..n]);
+            return Ok(n);
+        }
+        let n = buf.len().min(state.max_transfer);
+        state.generate(&mut buf[..n]);
+        Ok(n)
+    }
+
+    /// Answered from the read script; unscripted requests stall, as an
+    /// unsupported vendor request would.
+    fn read_control(&mut self, request_type: u8, request: u8, value: u16, index: u16, buf: &mut [u8], _timeout: Duration) -> Result<usize, QrngError> {
+        self.record(UsbCommand::ControlIn { request_type, request, value, index, len: buf.len() });
+        let mut state = self.state.lock().unwrap();
+        if !state.open {
+            return Err(QrngError::DeviceNotInitialized);
+        }
+        let data = state.script.pop_front().unwrap_or(Err(rusb::Error::Pipe))?;
+        let n = data.len().min(buf.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        Ok(n)
+    }
+
+    fn write_control(&mut self, request_type: u8, request: u8, value: u16, index: u16, buf: &[u8], timeout: Duration) -> Result<usize, QrngError> {
+        UsbHandle::write_control(self, request_type, request, value, index, buf, timeout)
+    }
+
+    fn bulk_in_endpoints(&self, interface: u8) -> Result<Vec<u8>, QrngError> {
+        Ok(self.state.lock().unwrap().bulk_in_endpoints.get(&interface).cloned().unwrap_or_default())
+    }
+
+    fn claim_interface(&mut self, iface: u8) -> Result<(), QrngError> {
+        UsbHandle::claim_interface(self, iface)
+    }
+
+    fn release_interface(&mut self, iface: u8) -> Result<(), QrngError> {
+        self.record(UsbCommand::ReleaseInterface(iface));
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        if state.open {
+            state.open = false;
+            let interface = state.interface;
+            state.commands.push(UsbCommand::ReleaseInterface(interface));
+        }
+    }
+
+    fn manufacturer(&mut self) -> Result<String, QrngError> {
+        self.string("FTDI")
+    }
+
+    fn description(&mut self) -> Result<String, QrngError> {
+        self.string("Mock QRNG")
+    }
+
+    fn serial(&mut self) -> Result<String, QrngError> {
+        Ok(self.serial.clone())
+    }
+}
+
+impl UsbHandle for MockTransport {
+    fn reset(&mut self) -> Result<(), QrngError> {
+        self.record(UsbCommand::Reset);
+        Ok(())
+    }
+
+    fn set_active_configuration(&mut self, config: u8) -> Result<(), QrngError> {
+        self.record(UsbCommand::SetConfiguration(config));
+        Ok(())
+    }
+
+    fn claim_interface(&mut self, iface: u8) -> Result<(), QrngError> {
+        self.record(UsbCommand::ClaimInterface(iface));
+        let mut state = self.state.lock().unwrap();
+        if state.busy_claims > 0 {
+            state.busy_claims -= 1;
+            return Err(rusb::Error::Busy.into());
+        }
+        Ok(())
+    }
+
+    fn write_control(&mut self, request_type: u8, request: u8, value: u16, index: u16, buf: &[u8], _timeout: Duration) -> Result<usize, QrngError> {
+        self.record(UsbCommand::Control { request_type, request, value, index, data: buf.to_vec() });
+        if self.state.lock().unwrap().control_stalls {
+            return Err(rusb::Error::Pipe.into());
+        }
+        Ok(buf.len())
+    }
+
+    fn write_bulk(&mut self, endpoint: u8, buf: &[u8], _timeout: Duration) -> Result<usize, QrngError> {
+        self.record(UsbCommand::BulkOut { endpoint, data: buf.to_vec() });
+        Ok(buf.len())
+    }
+
+    fn read_bulk(&mut self, endpoint: u8, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError> {
+        self.record(UsbCommand::BulkIn { endpoint, len: buf.len() });
+        Transport::read_bulk(self, endpoint, buf, timeout)
+    }
+}
+
+/// A QRNG stand-in for testing code built on this crate without hardware.
+/// Serves a reproducible xorshift stream determined by the seed, so two
+/// mocks with the same seed produce the same bytes. Clones share the
+/// stream.
+#[derive(Debug, Clone)]
+pub struct MockQrngDevice {
+    transport: MockTransport,
+}
+
+impl MockQrngDevice {
+    pub fn new(serial: &str, seed: u64) -> Self {
+        Self { transport: MockTransport::with_seed(serial, seed) }
+    }
+
+    pub fn serial(&self) -> &str {
+        &self.transport.serial
+    }
+
+    /// A `QrngDevice` reading from this mock's stream. Like one from a
+    /// scan, it needs `initialize` before reading.
+    pub fn device(&self) -> QrngDevice {
+        self.transport.device()
+    }
+}
+
+impl EntropySource for MockQrngDevice {
+    fn read(&self, size: usize) -> BoxFuture<'_, Result<Vec<u8>, QrngError>> {
+        let mut entropy = vec![0u8; size];
+        self.transport.state.lock().unwrap().generate(&mut entropy);
+        Box::pin(async move { Ok(entropy) })
+    }
+}
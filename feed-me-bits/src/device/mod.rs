@@ -1,48 +1,238 @@
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use rusb::{Context, Device, DeviceDescriptor, UsbContext};
-use tokio::sync::Mutex;
-use std::time::Duration;
-use tracing::{info, warn, error};
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit};
+use std::time::{Duration, Instant};
+use tracing::{info, instrument, warn, error};
 use crate::error::QrngError;
-use crate::{FTDI_VENDOR_ID, FTDI_PRODUCT_ID};
+use crate::estimate::{self, MinEntropyReport};
+use crate::health::{self, ContinuousRngTest, HealthMonitor, QualityReport};
+use crate::conditioning::{self, Sha256Conditioner};
+use crate::clock::{Clock, SystemClock};
+use crate::source::EntropySource;
 use std::collections::HashMap;
+use futures::Stream;
+use transport::{Transport, UsbTransport};
+use hotplug::HotplugWatch;
 
-#[derive(Debug)]
+pub use adaptive::AdaptiveChunking;
+pub use init::{InitSequence, FtdiInitSequence, UsbHandle};
+pub use error_rate::ErrorRateAlarm;
+pub use contention::RECLAIM_ATTEMPTS;
+pub use inventory::{Inventory, InventoryEntry, DeviceHealth, DeviceConfigSummary};
+pub use filter::DeviceFilter;
+pub use config::{DeviceConfig, DEFAULT_CONFIG_VALUE, DEFAULT_INTERFACE};
+pub use reconnect::RECONNECT_INTERVAL;
+#[cfg(feature = "mock")]
+pub use mock::MockQrngDevice;
+pub use retry::{RetryPolicy, RetryableError};
+pub use stats::DeviceStats;
+pub use benchmark::ThroughputReport;
+pub use info::DeviceInfo;
+pub use firmware::FirmwareQuery;
+pub use ftdi::{
+    ftdi_in_endpoint, FTDI_OUT_REQUEST_TYPE, SIO_RESET_PURGE_RX, SIO_RESET_PURGE_TX, SIO_RESET_REQUEST, SIO_SET_BITMODE_REQUEST,
+    SIO_SET_LATENCY_TIMER_REQUEST,
+};
+pub use status::{
+    StatusFrame, STATUS_CHECKSUM_OFFSET, STATUS_FRAME_LEN, STATUS_MAGIC, STATUS_TEMPERATURE_OFFSET, STATUS_TEMPERATURE_SCALE,
+    STATUS_VOLTAGE_OFFSET, STATUS_VOLTAGE_SCALE,
+};
+use error_rate::ErrorRate;
+use reconnect::Scanner;
+
+mod transport;
+mod hotplug;
+mod rng;
+mod adaptive;
+mod init;
+mod error_rate;
+mod inventory;
+mod contention;
+mod filter;
+mod config;
+mod reconnect;
+mod retry;
+mod stats;
+mod benchmark;
+mod info;
+mod firmware;
+mod ftdi;
+mod status;
+#[cfg(any(test, feature = "mock"))]
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) mod mock;
+
+/// Bulk-read timeout used unless overridden with `set_read_timeout`.
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Longest bulk transfer issued unless configured otherwise (64 KiB).
+/// Many FTDI setups fail or come up short on larger ones.
+pub const DEFAULT_MAX_TRANSFER: usize = 64 * 1024;
+
+/// Bulk IN endpoint entropy is read from unless configured or inferred
+/// otherwise.
+pub const DEFAULT_ENTROPY_ENDPOINT: u8 = 0x81;
+
+/// Bulk IN endpoint the status frame is read from unless configured or
+/// inferred otherwise.
+pub const DEFAULT_STATUS_ENDPOINT: u8 = 0x82;
+
+/// Serial reported by `DeviceManager::read_entropy_from_any` for bytes
+/// served by the fallback source.
+pub const FALLBACK_SERIAL: &str = "fallback";
+
+/// Default `RetryPolicy::backoff`, the delay before the first retry.
+pub const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_millis(10);
+
+/// Longest `read_entropy_retry` waits between attempts, unless the policy's
+/// `backoff` is longer.
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
 pub struct QrngDevice {
-    device: Arc<Mutex<Device<Context>>>,
-    descriptor: DeviceDescriptor,
+    transport: Arc<Mutex<Box<dyn Transport>>>,
+    /// Read once at construction so spans and `serial` don't need the
+    /// transport lock. `None` if the descriptor couldn't be read then.
+    serial: Option<String>,
+    vendor_id: u16,
+    product_id: u16,
+    /// Major and minor USB release from the device descriptor.
+    usb_version: (u8, u8),
+    /// Major and minor `bcdDevice` release from the device descriptor.
+    device_version: (u8, u8),
+    /// Where the device sits on the bus, fixed for as long as it stays
+    /// plugged in.
+    bus_number: u8,
+    address: u8,
     initialized: bool,
+    config: DeviceConfig,
+    /// Cleared once either endpoint is set explicitly.
+    infer_endpoints: bool,
+    health_monitor: Option<Arc<std::sync::Mutex<HealthMonitor>>>,
+    /// Run `health::startup_check` on a sample during `initialize`.
+    startup_check: bool,
+    /// Bytes read for the startup check.
+    startup_sample_size: usize,
+    /// Purge the FTDI FIFOs at the start of each `read_entropy`.
+    flush_before_read: bool,
+    /// Bounds how many `read_entropy` calls run at once, shared by clones.
+    read_permits: Arc<Semaphore>,
+    /// Runs `FtdiInitSequence` for `config` when unset.
+    init_sequence: Option<Arc<dyn InitSequence>>,
 }
 
-impl Clone for QrngDevice {
-    fn clone(&self) -> Self {
-        Self {
-            device: Arc::clone(&self.device),
-            descriptor: unsafe { std::ptr::read(&self.descriptor) },
-            initialized: self.initialized,
-        }
-    }
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeviceStatus {
     pub initialized: bool,
     pub temperature: f32,
     pub voltage: f32,
 }
 
+/// What a `DeviceManager` does with a device that disappears mid-read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisconnectStrategy {
+    /// Drop the device from the manager entirely.
+    #[default]
+    Remove,
+    /// Move the device aside so it is no longer served but can be inspected.
+    Quarantine,
+}
+
 #[derive(Clone)]
 pub struct DeviceManager {
     devices: Arc<Mutex<HashMap<String, QrngDevice>>>,
+    quarantine: Arc<Mutex<HashMap<String, QrngDevice>>>,
+    disconnect_strategy: DisconnectStrategy,
+    hotplug: Arc<std::sync::Mutex<Option<HotplugWatch>>>,
+    error_rates: Arc<Mutex<HashMap<String, ErrorRate>>>,
+    stats: Arc<Mutex<HashMap<String, DeviceStats>>>,
+    error_alarm: ErrorRateAlarm,
+    continuous_test: bool,
+    continuous_tests: Arc<Mutex<HashMap<String, ContinuousRngTest>>>,
+    relinquish_window: Option<Duration>,
+    /// Rotates the first device of each `read_entropy_balanced` call.
+    balance_cursor: Arc<AtomicUsize>,
+    fallback: Option<Arc<dyn EntropySource>>,
+    clock: Arc<dyn Clock>,
+    reconnect_attempts: usize,
+    scanner: Scanner,
+}
+
+impl std::fmt::Debug for DeviceManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceManager")
+            .field("disconnect_strategy", &self.disconnect_strategy)
+            .field("continuous_test", &self.continuous_test)
+            .field("fallback", &self.fallback)
+            .field("reconnect_attempts", &self.reconnect_attempts)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for DeviceManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DeviceManager {
     pub fn new() -> Self {
+        Self::with_disconnect_strategy(DisconnectStrategy::default())
+    }
+
+    pub fn with_disconnect_strategy(disconnect_strategy: DisconnectStrategy) -> Self {
         Self {
             devices: Arc::new(Mutex::new(HashMap::new())),
+            quarantine: Arc::new(Mutex::new(HashMap::new())),
+            disconnect_strategy,
+            hotplug: Arc::new(std::sync::Mutex::new(None)),
+            error_rates: Arc::new(Mutex::new(HashMap::new())),
+            stats: Arc::new(Mutex::new(HashMap::new())),
+            error_alarm: ErrorRateAlarm::default(),
+            continuous_test: false,
+            continuous_tests: Arc::new(Mutex::new(HashMap::new())),
+            relinquish_window: None,
+            balance_cursor: Arc::new(AtomicUsize::new(0)),
+            fallback: None,
+            clock: Arc::new(SystemClock),
+            reconnect_attempts: 0,
+            scanner: reconnect::usb_scanner(),
         }
     }
 
+    /// Thresholds for flagging a device as degraded. Applies to this handle
+    /// and clones made after the call.
+    pub fn set_error_rate_alarm(&mut self, alarm: ErrorRateAlarm) {
+        self.error_alarm = alarm;
+    }
+
+    /// Run the FIPS 140-2 continuous RNG test over every `read_entropy`,
+    /// failing reads that repeat a 32-bit block. Applies to this handle and
+    /// clones made after the call.
+    pub fn set_continuous_test(&mut self, enabled: bool) {
+        self.continuous_test = enabled;
+    }
+
+    /// Serve `read_entropy_any` from `source` when no device can. Applies to
+    /// this handle and clones made after the call.
+    pub fn set_fallback(&mut self, source: Arc<dyn EntropySource>) {
+        self.fallback = Some(source);
+    }
+
+    /// Whether reads may be served by a fallback source instead of a QRNG.
+    pub fn has_fallback(&self) -> bool {
+        self.fallback.as_ref().is_some_and(|source| source.is_fallback())
+    }
+
+    /// Time source for windowed bookkeeping such as the error rate.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Register `device` under its serial. Uses the serial cached at
+    /// construction, so this only reaches the device if that read failed.
     pub async fn add_device(&self, device: QrngDevice) -> Result<String, QrngError> {
         let serial = device.serial().await?;
         let mut devices = self.devices.lock().await;
@@ -50,12 +240,72 @@ impl DeviceManager {
         Ok(serial)
     }
 
+    /// Register an initialized `MockQrngDevice` seeded with `seed` under
+    /// `serial`, replacing any device already there.
+    #[cfg(any(test, feature = "mock"))]
+    pub async fn add_mock(&self, serial: &str, seed: u64) -> Result<String, QrngError> {
+        let mut device = mock::MockQrngDevice::new(serial, seed).device();
+        device.initialize().await?;
+        self.add_device(device).await
+    }
+
+    /// Remove `serial` and close its USB handle. Clones of the device held
+    /// elsewhere stop working too.
     pub async fn remove_device(&self, serial: &str) -> Result<(), QrngError> {
         let mut devices = self.devices.lock().await;
-        devices.remove(serial).ok_or_else(|| QrngError::DeviceNotFound(serial.to_string()))?;
+        let device = devices.remove(serial).ok_or_else(|| QrngError::DeviceNotFound(serial.to_string()))?;
+        device.close().await;
+        self.forget(serial).await;
         Ok(())
     }
 
+    /// Rescan the bus and bring the manager in line with it: new devices are
+    /// added, devices no longer present are removed and closed, and devices
+    /// still present keep their existing handle and `initialized` state.
+    /// Returns every managed serial, sorted.
+    pub async fn scan_and_register(&self) -> Result<Vec<String>, QrngError> {
+        self.register_scanned(scan_devices().await?).await
+    }
+
+    /// Reconcile the device map against `found`, leaving it untouched if any
+    /// serial can't be read.
+    pub(crate) async fn register_scanned(&self, found: Vec<QrngDevice>) -> Result<Vec<String>, QrngError> {
+        let mut scanned = HashMap::with_capacity(found.len());
+        for device in found {
+            scanned.insert(device.serial().await?, device);
+        }
+
+        let mut devices = self.devices.lock().await;
+        let gone: Vec<String> = devices.keys()
+            .filter(|serial| !scanned.contains_key(*serial))
+            .cloned()
+            .collect();
+        for serial in gone {
+            if let Some(device) = devices.remove(&serial) {
+                device.close().await;
+                info!("Removed device {} no longer on the bus", serial);
+            }
+            self.forget(&serial).await;
+        }
+        for (serial, device) in scanned {
+            devices.entry(serial).or_insert(device);
+        }
+
+        let mut serials: Vec<String> = devices.keys().cloned().collect();
+        serials.sort();
+        Ok(serials)
+    }
+
+    /// Shut down every managed device, releasing their interfaces. The
+    /// devices stay registered and can be initialized again.
+    pub async fn shutdown_all(&self) {
+        let mut devices = self.devices.lock().await;
+        for (serial, device) in devices.iter_mut() {
+            device.shutdown().await;
+            info!("Shut down device {}", serial);
+        }
+    }
+
     pub async fn get_device(&self, serial: &str) -> Result<QrngDevice, QrngError> {
         let devices = self.devices.lock().await;
         devices.get(serial)
@@ -68,137 +318,821 @@ impl DeviceManager {
         devices.keys().cloned().collect()
     }
 
+    /// Serials of devices set aside by `DisconnectStrategy::Quarantine`.
+    pub async fn quarantined_devices(&self) -> Vec<String> {
+        let quarantine = self.quarantine.lock().await;
+        quarantine.keys().cloned().collect()
+    }
+
     pub async fn initialize_device(&self, serial: &str) -> Result<(), QrngError> {
         let mut device = self.get_device(serial).await?;
-        device.initialize().await?;
+        self.initialize_cooperatively(&mut device).await?;
         self.add_device(device).await?;
         Ok(())
     }
 
     pub async fn read_entropy(&self, serial: &str, size: usize) -> Result<Vec<u8>, QrngError> {
         let device = self.get_device(serial).await?;
-        device.read_entropy(size).await
+        let mut result = device.read_entropy(size).await;
+        if self.should_reconnect(&result) {
+            if let Ok(device) = self.reconnect(serial).await {
+                result = device.read_entropy(size).await;
+            }
+        }
+        if let (true, Ok(entropy)) = (self.continuous_test, &result) {
+            let mut tests = self.continuous_tests.lock().await;
+            if let Err(e) = tests.entry(serial.to_string()).or_default().check_all(entropy) {
+                error!("Entropy from {} rejected: {}", serial, e);
+                result = Err(e);
+            }
+        }
+        if !matches!(result, Err(QrngError::DeviceNotInitialized)) {
+            self.record_read(serial, result.is_err()).await;
+        }
+        let now = self.clock.now();
+        self.stats.lock().await.entry(serial.to_string()).or_default().record(&result, now);
+        if let Err(QrngError::DeviceDisconnected) = result {
+            self.handle_disconnect(serial).await;
+        }
+        result
     }
 
+    /// Share of reads from `serial` that failed within the alarm window.
+    pub async fn error_rate(&self, serial: &str) -> Result<f64, QrngError> {
+        self.get_device(serial).await?;
+        let now = self.clock.now();
+        let mut error_rates = self.error_rates.lock().await;
+        Ok(error_rates.get_mut(serial).map_or(0.0, |rate| rate.rate_at(&self.error_alarm, now)))
+    }
+
+    /// Whether `serial`'s error rate has crossed the alarm threshold.
+    pub async fn is_degraded(&self, serial: &str) -> Result<bool, QrngError> {
+        self.get_device(serial).await?;
+        let error_rates = self.error_rates.lock().await;
+        Ok(error_rates.get(serial).is_some_and(|rate| rate.is_degraded()))
+    }
+
+    /// Read from the first available device in serial order, moving on to the
+    /// next one if a device fails or is unplugged mid-read, and to the
+    /// fallback if every device fails.
+    pub async fn read_entropy_any(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        self.read_entropy_from_any(size).await.map(|(_, entropy)| entropy)
+    }
+
+    /// Like `read_entropy_any`, also returning the serial of the device that
+    /// served the read, or `FALLBACK_SERIAL` if no device could. Without a
+    /// fallback, fails with the last device's error.
+    pub async fn read_entropy_from_any(&self, size: usize) -> Result<(String, Vec<u8>), QrngError> {
+        let mut serials = self.list_devices().await;
+        serials.sort();
+        let mut last_error = None;
+        for serial in serials {
+            match self.read_entropy(&serial, size).await {
+                Ok(entropy) => return Ok((serial, entropy)),
+                Err(QrngError::DeviceDisconnected) => {
+                    warn!("Device {} disconnected, retrying on another device", serial);
+                    last_error = Some(QrngError::DeviceDisconnected);
+                }
+                // Lost a race with another reader that already removed it
+                Err(QrngError::DeviceNotFound(_)) => {}
+                Err(e) => {
+                    warn!("Read from {} failed, retrying on another device: {}", serial, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        match &self.fallback {
+            Some(fallback) => Ok((FALLBACK_SERIAL.to_string(), fallback.read(size).await?)),
+            None => Err(last_error.unwrap_or_else(|| QrngError::DeviceNotFound("no devices available".to_string()))),
+        }
+    }
+
+    /// Spread one read across every initialized device, in serial order,
+    /// concatenating their shares. A failing device is dropped for the rest
+    /// of the call and its share redistributed to the others.
+    pub async fn read_entropy_pooled(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        self.read_spread(size, false).await
+    }
+
+    /// Like `read_entropy_pooled`, but each call starts one device further
+    /// along, so repeated reads smaller than the device count still draw on
+    /// every device in turn.
+    pub async fn read_entropy_balanced(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        self.read_spread(size, true).await
+    }
+
+    async fn read_spread(&self, size: usize, rotate: bool) -> Result<Vec<u8>, QrngError> {
+        if size == 0 {
+            return Err(QrngError::InvalidState("Invalid entropy size".to_string()));
+        }
+
+        let mut healthy: Vec<String> = self.initialized_devices().await
+            .into_iter()
+            .map(|(serial, _)| serial)
+            .collect();
+        if healthy.is_empty() {
+            return Err(QrngError::DeviceNotFound("no initialized devices".to_string()));
+        }
+        if rotate {
+            let start = self.balance_cursor.fetch_add(1, Ordering::Relaxed) % healthy.len();
+            healthy.rotate_left(start);
+        }
+
+        let mut output = Vec::with_capacity(size);
+        let mut last_error = None;
+        while output.len() < size && !healthy.is_empty() {
+            let share = (size - output.len()).div_ceil(healthy.len());
+            let before = output.len();
+            let mut failed = Vec::new();
+            for (index, serial) in healthy.iter().enumerate() {
+                let wanted = share.min(size - output.len());
+                if wanted == 0 {
+                    break;
+                }
+                // Through the manager, so each share gets the continuous
+                // test, stats, the error-rate alarm and reconnects
+                match self.read_entropy(serial, wanted).await {
+                    Ok(chunk) => output.extend(chunk),
+                    Err(e) => {
+                        warn!("Pooled read from {} failed, falling back to remaining devices: {}", serial, e);
+                        failed.push(index);
+                        last_error = Some(e);
+                    }
+                }
+            }
+            for index in failed.into_iter().rev() {
+                healthy.remove(index);
+            }
+            if output.len() == before {
+                break;
+            }
+        }
+
+        if output.len() < size {
+            return Err(last_error.unwrap_or_else(|| {
+                QrngError::CommunicationError(format!("Pooled read returned {} of {} bytes", output.len(), size))
+            }));
+        }
+        Ok(output)
+    }
+
+    /// Continuous entropy from one device, see `QrngDevice::entropy_stream`.
+    pub async fn entropy_stream(&self, serial: &str, chunk_size: usize) -> Result<impl Stream<Item = Result<Vec<u8>, QrngError>>, QrngError> {
+        let device = self.get_device(serial).await?;
+        Ok(device.entropy_stream(chunk_size))
+    }
+
+    /// A device found unplugged is handled as in `read_entropy`.
     pub async fn get_device_status(&self, serial: &str) -> Result<DeviceStatus, QrngError> {
         let device = self.get_device(serial).await?;
-        device.status().await
+        let result = device.status().await;
+        if let Err(QrngError::DeviceDisconnected) = result {
+            self.handle_disconnect(serial).await;
+        }
+        result
+    }
+
+    /// Read `sample_size` bytes and estimate their min-entropy.
+    /// `QrngDevice::measure_quality` for `serial`, with the sample read
+    /// counted like any other read.
+    pub async fn sample_entropy_quality(&self, serial: &str, sample_size: usize) -> Result<QualityReport, QrngError> {
+        let sample = self.read_entropy(serial, sample_size).await?;
+        Ok(QualityReport::measure(&sample))
+    }
+
+    pub async fn min_entropy_report(&self, serial: &str, sample_size: usize) -> Result<MinEntropyReport, QrngError> {
+        let sample = self.read_entropy(serial, sample_size).await?;
+        Ok(estimate::min_entropy_report(&sample))
+    }
+
+    async fn record_read(&self, serial: &str, failed: bool) {
+        let now = self.clock.now();
+        let mut error_rates = self.error_rates.lock().await;
+        let rate = error_rates.entry(serial.to_string()).or_default();
+        match rate.record(&self.error_alarm, now, failed) {
+            Some(true) => warn!(
+                serial,
+                error_rate = rate.rate_at(&self.error_alarm, now),
+                "Device degraded: read error rate above threshold"
+            ),
+            Some(false) => info!(serial, "Device recovered: read error rate back below threshold"),
+            None => {}
+        }
+    }
+
+    // Drop per-device bookkeeping once a device leaves the manager
+    async fn forget(&self, serial: &str) {
+        self.error_rates.lock().await.remove(serial);
+        self.continuous_tests.lock().await.remove(serial);
+        self.stats.lock().await.remove(serial);
+    }
+
+    async fn initialized_devices(&self) -> Vec<(String, QrngDevice)> {
+        let devices = self.devices.lock().await;
+        let mut initialized: Vec<_> = devices.iter()
+            .filter(|(_, device)| device.is_initialized())
+            .map(|(serial, device)| (serial.clone(), device.clone()))
+            .collect();
+        initialized.sort_by(|a, b| a.0.cmp(&b.0));
+        initialized
+    }
+
+    async fn handle_disconnect(&self, serial: &str) {
+        let removed = self.devices.lock().await.remove(serial);
+        let Some(device) = removed else {
+            return;
+        };
+        match self.disconnect_strategy {
+            DisconnectStrategy::Remove => {
+                info!("Removed disconnected device {}", serial);
+            }
+            DisconnectStrategy::Quarantine => {
+                info!("Quarantined disconnected device {}", serial);
+                self.quarantine.lock().await.insert(serial.to_string(), device);
+            }
+        }
     }
 }
 
 impl QrngDevice {
     pub fn new(device: Device<Context>, descriptor: DeviceDescriptor) -> Self {
+        Self::with_timeout(device, descriptor, DEFAULT_READ_TIMEOUT)
+    }
+
+    pub fn with_timeout(device: Device<Context>, descriptor: DeviceDescriptor, timeout: Duration) -> Self {
+        let mut qrng_device = Self::from_transport(Box::new(UsbTransport::new(device, descriptor)));
+        qrng_device.set_read_timeout(timeout);
+        qrng_device
+    }
+
+    pub fn new_with_config(device: Device<Context>, descriptor: DeviceDescriptor, config: DeviceConfig) -> Self {
+        let mut qrng_device = Self::from_transport(Box::new(UsbTransport::new(device, descriptor)));
+        qrng_device.set_config(config);
+        qrng_device
+    }
+
+    pub(crate) fn from_transport(mut transport: Box<dyn Transport>) -> Self {
+        let serial = match transport.serial() {
+            Ok(serial) => Some(serial),
+            Err(e) => {
+                warn!("Failed to read serial number: {}", e);
+                None
+            }
+        };
         Self {
-            device: Arc::new(Mutex::new(device)),
-            descriptor,
+            serial,
+            vendor_id: transport.vendor_id(),
+            product_id: transport.product_id(),
+            usb_version: transport.usb_version(),
+            device_version: transport.device_version(),
+            bus_number: transport.bus_number(),
+            address: transport.address(),
+            transport: Arc::new(Mutex::new(transport)),
             initialized: false,
+            config: DeviceConfig::default(),
+            infer_endpoints: true,
+            health_monitor: None,
+            startup_check: true,
+            startup_sample_size: health::STARTUP_SAMPLE_SIZE,
+            flush_before_read: false,
+            read_permits: Arc::new(Semaphore::new(1)),
+            init_sequence: None,
         }
     }
 
+    /// Replace the whole USB layout. Takes effect for reads at once and for
+    /// the configuration and interface on the next `initialize`. The
+    /// endpoints given are used as-is rather than inferred.
+    pub fn set_config(&mut self, config: DeviceConfig) {
+        self.config = config;
+        self.infer_endpoints = false;
+    }
+
+    pub fn config(&self) -> DeviceConfig {
+        self.config
+    }
+
+    /// Claim channel `interface` of a multi-channel chip such as the
+    /// FT2232 or FT4232 instead of the first. Endpoints are still inferred
+    /// from that interface's descriptors, with entropy read from the
+    /// channel's standard FTDI IN endpoint if they can't be.
+    pub fn with_interface(mut self, interface: u8) -> Self {
+        self.config.interface = interface;
+        self.config.entropy_endpoint = ftdi_in_endpoint(interface);
+        self
+    }
+
+    /// Timeout applied to each bulk read in `read_entropy` and `status`.
+    /// `read_entropy` also gives up on topping up short reads once it
+    /// passes, so raise it for devices asked for large reads, or use
+    /// `read_entropy_with_timeout` for one-off large reads.
+    pub fn set_read_timeout(&mut self, timeout: Duration) {
+        self.config.read_timeout = timeout;
+    }
+
+    pub fn read_timeout(&self) -> Duration {
+        self.config.read_timeout
+    }
+
+    /// Bulk IN endpoint read by `read_entropy`. Setting either endpoint
+    /// stops `initialize` from inferring them from the descriptors.
+    pub fn set_entropy_endpoint(&mut self, endpoint: u8) {
+        self.config.entropy_endpoint = endpoint;
+        self.infer_endpoints = false;
+    }
+
+    pub fn entropy_endpoint(&self) -> u8 {
+        self.config.entropy_endpoint
+    }
+
+    /// Bulk IN endpoint read by `status`, see `set_entropy_endpoint`.
+    pub fn set_status_endpoint(&mut self, endpoint: u8) {
+        self.config.status_endpoint = endpoint;
+        self.infer_endpoints = false;
+    }
+
+    pub fn status_endpoint(&self) -> u8 {
+        self.config.status_endpoint
+    }
+
+    /// Replace the default FTDI init sequence run by `initialize`, for
+    /// firmwares that need different setup commands. The sequence is
+    /// expected to claim the configured interface.
+    pub fn set_init_sequence(&mut self, sequence: Arc<dyn InitSequence>) {
+        self.init_sequence = Some(sequence);
+    }
+
+    /// Run every byte returned by `read_entropy` through `monitor`. Clones
+    /// made afterwards share the monitor's state.
+    pub fn set_health_monitor(&mut self, monitor: HealthMonitor) {
+        self.health_monitor = Some(Arc::new(std::sync::Mutex::new(monitor)));
+    }
+
+    /// Whether `initialize` reads a sample and rejects devices that fail
+    /// `health::startup_check`. On by default.
+    pub fn set_startup_check(&mut self, enabled: bool) {
+        self.startup_check = enabled;
+    }
+
+    /// Bytes the startup check samples, `STARTUP_SAMPLE_SIZE` by default.
+    /// Raised to at least `STARTUP_MIN_DISTINCT` bytes, as tiny samples
+    /// prove little.
+    pub fn set_startup_sample_size(&mut self, bytes: usize) {
+        self.startup_sample_size = bytes.max(health::STARTUP_MIN_DISTINCT);
+    }
+
+    /// Whether `read_entropy` calls `flush` first, so bytes left in the
+    /// FIFO by an earlier session aren't served. Off by default, as it adds
+    /// two control transfers to every read.
+    pub fn set_flush_before_read(&mut self, enabled: bool) {
+        self.flush_before_read = enabled;
+    }
+
+    /// How many `read_entropy` calls may be in flight at once, 1 by
+    /// default. Individual transfers are always serialized by the device
+    /// lock, but a read made of several transfers can interleave with
+    /// another's unless this is 1. Applies to this handle and clones made
+    /// after the call.
+    pub fn set_max_concurrent_reads(&mut self, reads: usize) {
+        self.read_permits = Arc::new(Semaphore::new(reads.max(1)));
+    }
+
+    /// Open the device and run its init sequence. Unless set explicitly, the
+    /// entropy and status endpoints are taken to be the first and second bulk
+    /// IN endpoints of the active configuration, keeping the defaults for any
+    /// the descriptors don't provide.
+    ///
+    /// The configured latency timer and bit mode are applied next; a chip
+    /// refusing them fails initialization.
+    ///
+    /// With the startup check enabled, a device whose first
+    /// `set_startup_sample_size` bytes fail it is closed again and left
+    /// uninitialized.
+    #[instrument(skip(self), fields(serial = self.span_serial()))]
     pub async fn initialize(&mut self) -> Result<(), QrngError> {
-        let device = self.device.lock().await;
-        let handle = device.open()?;
-        
-        // Reset device
-        handle.reset()?;
-        
-        // Set configuration
-        handle.set_active_configuration(1)?;
-        
-        // Claim interface
-        handle.claim_interface(0)?;
-        
+        self.open().await?;
         self.initialized = true;
+
+        if let Err(e) = self.configure_ftdi().await {
+            self.shutdown().await;
+            warn!("Failed to configure the FTDI chip: {}", e);
+            return Err(e);
+        }
+
+        if self.startup_check {
+            let result = match self.read_entropy(self.startup_sample_size).await {
+                Ok(sample) => health::startup_check(&sample),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                self.shutdown().await;
+                warn!("QRNG device failed its startup check: {}", e);
+                return Err(e);
+            }
+        }
+
         info!("QRNG device initialized successfully");
         Ok(())
     }
 
+    async fn open(&mut self) -> Result<(), QrngError> {
+        let mut transport = self.transport.lock().await;
+        self.run_init_sequence(transport.as_mut())?;
+
+        if self.infer_endpoints {
+            match transport.bulk_in_endpoints(self.config.interface) {
+                Ok(endpoints) => {
+                    if let Some(&endpoint) = endpoints.first() {
+                        self.config.entropy_endpoint = endpoint;
+                    }
+                    if let Some(&endpoint) = endpoints.get(1) {
+                        self.config.status_endpoint = endpoint;
+                    }
+                }
+                Err(e) => warn!("Failed to read endpoint descriptors, using defaults: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    fn run_init_sequence(&self, transport: &mut dyn Transport) -> Result<(), QrngError> {
+        let default_sequence = FtdiInitSequence {
+            configuration: self.config.config_value,
+            interface: self.config.interface,
+        };
+        let sequence = self.init_sequence.as_deref().unwrap_or(&default_sequence);
+        transport.initialize(sequence, self.config.interface)
+    }
+
+    /// Read exactly `size` bytes. Short transfers are topped up with further
+    /// reads until the read timeout runs out, after which the read fails
+    /// rather than returning fewer bytes.
+    #[instrument(skip(self), fields(serial = self.span_serial()))]
     pub async fn read_entropy(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        self.read_entropy_with_timeout(size, self.config.read_timeout).await
+    }
+
+    /// `read_entropy` with `timeout` in place of the configured read timeout
+    /// for this call only. The timeout is a deadline for the whole read,
+    /// which is split into transfers of at most `max_transfer` bytes, so
+    /// very large reads need a timeout that scales with `size`. A transfer
+    /// that runs into the deadline fails the read with a `Timeout` counting
+    /// the bytes received so far.
+    pub async fn read_entropy_with_timeout(&self, size: usize, timeout: Duration) -> Result<Vec<u8>, QrngError> {
+        let _permit = self.read_permit().await?;
+        if self.flush_before_read {
+            self.flush().await?;
+        }
+        let mut buffer = vec![0u8; size];
+        let started = Instant::now();
+        let deadline = started + timeout;
+        let mut filled = 0;
+        let mut transfer_timeout = timeout;
+        loop {
+            match self.read_into_within(&mut buffer[filled..], transfer_timeout).await {
+                Ok(n) => filled += n,
+                // Report timeouts against the whole read, not the last transfer
+                Err(QrngError::Timeout { .. }) => {
+                    return Err(QrngError::Timeout { requested: size, received: filled, elapsed: started.elapsed() });
+                }
+                Err(e) => return Err(e),
+            }
+            if filled == size {
+                return Ok(buffer);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(QrngError::CommunicationError(format!(
+                    "Short read: got {} of {} bytes within {:?}", filled, size, timeout
+                )));
+            }
+            transfer_timeout = deadline - now;
+        }
+    }
+
+    /// Fill `buf` straight from the device without allocating. Returns the
+    /// number of bytes the device delivered, which may be less than
+    /// `buf.len()`; bytes past that count are left untouched.
+    pub async fn read_entropy_into(&self, buf: &mut [u8]) -> Result<usize, QrngError> {
+        let _permit = self.read_permit().await?;
+        self.read_into_within(buf, self.config.read_timeout).await
+    }
+
+    // Held for the whole of a read, so one made of several transfers isn't
+    // interleaved with another; see `set_max_concurrent_reads`
+    async fn read_permit(&self) -> Result<SemaphorePermit<'_>, QrngError> {
+        self.read_permits.acquire().await
+            .map_err(|_| QrngError::InvalidState("Read permits closed".to_string()))
+    }
+
+    async fn read_into_within(&self, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError> {
         if !self.initialized {
             return Err(QrngError::DeviceNotInitialized);
         }
 
-        if size == 0 {
+        if buf.is_empty() {
             return Err(QrngError::InvalidState("Invalid entropy size".to_string()));
         }
 
-        let device = self.device.lock().await;
-        let handle = device.open()?;
-        let mut buffer = vec![0u8; size];
-        let timeout = Duration::from_millis(1000);
-        
-        match handle.read_bulk(0x81, &mut buffer, timeout) {
-            Ok(_) => {
-                info!("Successfully read {} bytes of entropy", size);
-                Ok(buffer)
+        let mut transport = self.transport.lock().await;
+        self.read_locked(transport.as_mut(), buf, timeout)
+    }
+
+    // One bulk read with the transport lock already held
+    fn read_locked(&self, transport: &mut dyn Transport, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError> {
+        let len = buf.len().min(self.config.max_transfer.max(1));
+        let buf = &mut buf[..len];
+        let started = Instant::now();
+        match transport.read_bulk(self.config.entropy_endpoint, buf, timeout) {
+            Ok(n) => {
+                if let Some(monitor) = &self.health_monitor {
+                    if let Err(e) = monitor.lock().unwrap().feed(&buf[..n]) {
+                        error!("Entropy rejected: {}", e);
+                        return Err(QrngError::InvalidState(e.to_string()));
+                    }
+                }
+                if n < buf.len() {
+                    warn!("Short read: {} of {} bytes of entropy", n, buf.len());
+                } else {
+                    info!("Successfully read {} bytes of entropy", n);
+                }
+                Ok(n)
             }
-            Err(e) => {
+            Err(QrngError::UsbError(rusb::Error::NoDevice)) => {
+                error!("Device disconnected while reading entropy");
+                Err(QrngError::DeviceDisconnected)
+            }
+            Err(QrngError::UsbError(rusb::Error::Timeout)) => {
+                let elapsed = started.elapsed();
+                warn!("Timed out reading entropy after {:?}", elapsed);
+                Err(QrngError::Timeout { requested: buf.len(), received: 0, elapsed })
+            }
+            Err(QrngError::UsbError(e)) => {
                 error!("Error reading entropy: {}", e);
                 Err(QrngError::CommunicationError(e.to_string()))
             }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// One buffer of each of `sizes`, read back to back under a single
+    /// acquisition of the device lock, taken before any flush, so other
+    /// callers can't interleave.
+    /// Each buffer gets the full read timeout for its top-up reads.
+    pub async fn read_entropy_batch(&self, sizes: &[usize]) -> Result<Vec<Vec<u8>>, QrngError> {
+        if !self.initialized {
+            return Err(QrngError::DeviceNotInitialized);
+        }
+        if sizes.contains(&0) {
+            return Err(QrngError::InvalidState("Invalid entropy size".to_string()));
+        }
+
+        let _permit = self.read_permit().await?;
+        let mut transport = self.transport.lock().await;
+        if self.flush_before_read {
+            self.flush_locked(transport.as_mut())?;
+        }
+        let timeout = self.config.read_timeout;
+        let mut buffers = Vec::with_capacity(sizes.len());
+        for &size in sizes {
+            let mut buffer = vec![0u8; size];
+            let deadline = Instant::now() + timeout;
+            let mut filled = self.read_locked(transport.as_mut(), &mut buffer, timeout)?;
+            while filled < size {
+                if Instant::now() >= deadline {
+                    return Err(QrngError::CommunicationError(format!(
+                        "Short read: got {} of {} bytes within {:?}", filled, size, timeout
+                    )));
+                }
+                filled += self.read_locked(transport.as_mut(), &mut buffer[filled..], timeout)?;
+            }
+            buffers.push(buffer);
         }
+        Ok(buffers)
+    }
+
+    /// Read `size` bytes that have passed the SP 800-90B health tests.
+    ///
+    /// Uses the monitor from `set_health_monitor` when one is set, so test
+    /// state carries across reads. Otherwise the bytes are checked by a fresh
+    /// monitor assuming full entropy.
+    pub async fn read_entropy_checked(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        let entropy = self.read_entropy(size).await?;
+        if self.health_monitor.is_none() {
+            if let Err(e) = HealthMonitor::for_min_entropy(8.0).feed(&entropy) {
+                error!("Entropy rejected: {}", e);
+                return Err(QrngError::InvalidState(e.to_string()));
+            }
+        }
+        Ok(entropy)
+    }
+
+    /// Read `size` bytes of von Neumann debiased entropy.
+    ///
+    /// Debiasing discards a data-dependent share of the raw input (about
+    /// three quarters for an unbiased source), so the number of raw reads is
+    /// not fixed. Gives up with `InvalidState` if the device produces too
+    /// little usable output, as a stuck source would.
+    pub async fn read_debiased(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        let mut output = self.read_entropy_debiased(size).await?;
+        output.truncate(size);
+        Ok(output)
+    }
+
+    /// Read raw blocks until at least `min_output_bytes` of von Neumann
+    /// debiased entropy have accumulated, and return all of it.
+    ///
+    /// The output length is variable: the last block usually overshoots
+    /// `min_output_bytes`. Use `read_debiased` for an exact length. Fails
+    /// like `read_debiased` when the source yields too little output.
+    pub async fn read_entropy_debiased(&self, min_output_bytes: usize) -> Result<Vec<u8>, QrngError> {
+        const RAW_BUDGET_FACTOR: usize = 64;
+
+        let mut output = Vec::with_capacity(min_output_bytes);
+        let mut raw_read = 0;
+        while output.len() < min_output_bytes {
+            if raw_read >= min_output_bytes * RAW_BUDGET_FACTOR {
+                return Err(QrngError::InvalidState("Debiasing produced too little output".to_string()));
+            }
+            let raw = self.read_entropy((min_output_bytes - output.len()) * 4).await?;
+            raw_read += raw.len().max(1);
+            output.extend(conditioning::von_neumann(&raw));
+        }
+        Ok(output)
+    }
+
+    /// Read `size` bytes conditioned with SHA-256. Twice as many raw bytes
+    /// are read as returned, so each output byte is backed by two input bytes.
+    pub async fn read_entropy_conditioned(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        let raw = self.read_entropy(size * 2).await?;
+        let mut conditioner = Sha256Conditioner::new();
+        conditioner.absorb(&raw);
+        Ok(conditioner.extract(size))
+    }
+
+    /// Read `sample_size` bytes and score their Shannon and min-entropy.
+    pub async fn measure_quality(&self, sample_size: usize) -> Result<QualityReport, QrngError> {
+        let sample = self.read_entropy(sample_size).await?;
+        Ok(QualityReport::measure(&sample))
+    }
+
+    /// Continuous entropy in `chunk_size` blocks. A block is only read when
+    /// the stream is polled. A failed read is yielded as an error and ends the
+    /// stream.
+    pub fn entropy_stream(&self, chunk_size: usize) -> impl Stream<Item = Result<Vec<u8>, QrngError>> {
+        futures::stream::unfold(Some(self.clone()), move |device| async move {
+            let device = device?;
+            match device.read_entropy(chunk_size).await {
+                Ok(chunk) => Some((Ok(chunk), Some(device))),
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+
+    /// Blocking variant of `read_entropy` for synchronous callers such as
+    /// `RngCore`. Must not be called from within an async task.
+    pub fn read_entropy_blocking(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        futures::executor::block_on(self.read_entropy(size))
     }
 
+    /// Read and decode a `StatusFrame`. A short frame, one with the wrong
+    /// header or, unless `verify_checksum` is off, a bad checksum is a
+    /// `ProtocolError`. Firmware without a status endpoint gives
+    /// `StatusUnsupported`.
+    #[instrument(skip(self), fields(serial = self.span_serial()))]
     pub async fn status(&self) -> Result<DeviceStatus, QrngError> {
-        let device = self.device.lock().await;
-        let handle = device.open()?;
+        let mut transport = self.transport.lock().await;
         
         // Read status from device
-        let mut buffer = [0u8; 2];
-        let timeout = Duration::from_millis(100);
+        let mut buffer = [0u8; STATUS_CHECKSUM_OFFSET + 1];
         
-        match handle.read_bulk(0x82, &mut buffer, timeout) {
-            Ok(_) => Ok(DeviceStatus {
-                initialized: self.initialized,
-                temperature: buffer[0] as f32,
-                voltage: buffer[1] as f32 / 10.0,
-            }),
+        let started = Instant::now();
+        match transport.read_bulk(self.config.status_endpoint, &mut buffer, self.config.read_timeout) {
+            Ok(n) => {
+                let frame = if self.config.verify_checksum {
+                    StatusFrame::parse_checked(&buffer[..n])?
+                } else {
+                    StatusFrame::parse(&buffer[..n])?
+                };
+                Ok(DeviceStatus { initialized: self.initialized, ..frame })
+            }
+            Err(QrngError::UsbError(rusb::Error::Timeout)) => {
+                let elapsed = started.elapsed();
+                warn!("Timed out reading device status after {:?}", elapsed);
+                Err(QrngError::Timeout { requested: buffer.len(), received: 0, elapsed })
+            }
+            Err(QrngError::UsbError(rusb::Error::NoDevice)) => {
+                error!("Device disconnected while reading status");
+                Err(QrngError::DeviceDisconnected)
+            }
+            // Firmware without a status endpoint stalls the read
+            Err(QrngError::UsbError(e @ (rusb::Error::Pipe | rusb::Error::NotSupported))) => {
+                warn!("Device status not supported: {}", e);
+                Err(QrngError::StatusUnsupported)
+            }
             Err(e) => {
                 warn!("Error reading device status: {}", e);
-                Ok(DeviceStatus {
-                    initialized: self.initialized,
-                    temperature: 0.0,
-                    voltage: 0.0,
-                })
+                Err(e)
             }
         }
     }
 
+    /// Release the interface and close the USB handle shared by this device
+    /// and its clones. The device must be initialized again before reuse.
+    pub async fn close(&self) {
+        self.transport.lock().await.close();
+    }
+
+    /// `close` the device and mark it uninitialized, so reads fail with
+    /// `DeviceNotInitialized` rather than touching a released interface.
+    pub async fn shutdown(&mut self) {
+        self.close().await;
+        self.initialized = false;
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
     pub fn vendor_id(&self) -> u16 {
-        self.descriptor.vendor_id()
+        self.vendor_id
     }
 
     pub fn product_id(&self) -> u16 {
-        self.descriptor.product_id()
+        self.product_id
+    }
+
+    /// Major and minor USB release the device reports, e.g. `(2, 0)`.
+    pub fn usb_version(&self) -> (u8, u8) {
+        self.usb_version
+    }
+
+    /// Bus and address tell apart devices that report the same serial.
+    pub fn bus_number(&self) -> u8 {
+        self.bus_number
+    }
+
+    pub fn address(&self) -> u8 {
+        self.address
     }
 
     pub async fn manufacturer(&self) -> Result<String, QrngError> {
-        let device = self.device.lock().await;
-        let handle = device.open()?;
-        Ok(handle.read_manufacturer_string_ascii(&self.descriptor)?)
+        let mut transport = self.transport.lock().await;
+        transport.manufacturer()
     }
 
     pub async fn description(&self) -> Result<String, QrngError> {
-        let device = self.device.lock().await;
-        let handle = device.open()?;
-        Ok(handle.read_product_string_ascii(&self.descriptor)?)
+        let mut transport = self.transport.lock().await;
+        transport.description()
     }
 
+    /// The serial read at construction, or read now if that failed.
     pub async fn serial(&self) -> Result<String, QrngError> {
-        let device = self.device.lock().await;
-        let handle = device.open()?;
-        Ok(handle.read_serial_number_string_ascii(&self.descriptor)?)
+        if let Some(serial) = self.serial_cached() {
+            return Ok(serial.to_string());
+        }
+        let mut transport = self.transport.lock().await;
+        transport.serial()
+    }
+
+    /// The serial read at construction, without touching the device.
+    /// `None` if it couldn't be read then; `serial` retries.
+    pub fn serial_cached(&self) -> Option<&str> {
+        self.serial.as_deref()
+    }
+
+    fn span_serial(&self) -> &str {
+        self.serial_cached().unwrap_or("unknown")
     }
 }
 
+#[instrument]
 pub async fn scan_devices() -> Result<Vec<QrngDevice>, QrngError> {
+    scan_devices_filtered(&DeviceFilter::default()).await
+}
+
+/// Find the devices on the bus accepted by `filter`.
+pub async fn scan_devices_filtered(filter: &DeviceFilter) -> Result<Vec<QrngDevice>, QrngError> {
+    let qrng_devices = scan_devices_matching(|descriptor| filter.matches(descriptor)).await?;
+    Ok(filter.retain_allowed(qrng_devices).await)
+}
+
+/// Find FTDI devices with any of `product_ids`. An empty slice matches
+/// every FTDI product.
+pub async fn scan_devices_with_pids(product_ids: &[u16]) -> Result<Vec<QrngDevice>, QrngError> {
+    scan_devices_filtered(&DeviceFilter::with_product_ids(product_ids)).await
+}
+
+/// Find the devices on the bus whose descriptor passes `filter`, for
+/// matching rules a `DeviceFilter` can't express.
+pub async fn scan_devices_matching(filter: impl Fn(&DeviceDescriptor) -> bool) -> Result<Vec<QrngDevice>, QrngError> {
     let context = Context::new()?;
     let devices = context.devices()?;
     let mut qrng_devices = Vec::new();
 
     for device in devices.iter() {
         let descriptor = device.device_descriptor()?;
-        if descriptor.vendor_id() == FTDI_VENDOR_ID && descriptor.product_id() == FTDI_PRODUCT_ID {
+        if filter(&descriptor) {
             let qrng_device = QrngDevice::new(device, descriptor);
             info!("Found QRNG device: vendor={:04x}, product={:04x}", 
                 qrng_device.vendor_id(), 
@@ -207,7 +1141,6 @@ pub async fn scan_devices() -> Result<Vec<QrngDevice>, QrngError> {
             qrng_devices.push(qrng_device);
         }
     }
-
     info!("Found {} QRNG device(s)", qrng_devices.len());
     Ok(qrng_devices)
 }
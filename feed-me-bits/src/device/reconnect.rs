@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use crate::error::QrngError;
+use crate::source::BoxFuture;
+use super::{scan_devices, DeviceManager, QrngDevice};
+
+/// Wait between rescans while a reconnecting device hasn't reappeared.
+pub const RECONNECT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Lists the devices on the bus. Replaced in tests.
+pub(crate) type Scanner = Arc<dyn Fn() -> BoxFuture<'static, Result<Vec<QrngDevice>, QrngError>> + Send + Sync>;
+
+pub(crate) fn usb_scanner() -> Scanner {
+    Arc::new(|| Box::pin(scan_devices()))
+}
+
+impl DeviceManager {
+    /// How many rescans `read_entropy` makes for a device whose read fails
+    /// with a disconnect or communication error, e.g. after a brief unplug.
+    /// If the same serial turns up it is reopened, reinitialized and the
+    /// read retried once; otherwise the error is returned. 0, the default,
+    /// turns reconnection off. Applies to this handle and clones made after
+    /// the call.
+    pub fn set_reconnect_attempts(&mut self, attempts: usize) {
+        self.reconnect_attempts = attempts;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_scanner(&mut self, scanner: Scanner) {
+        self.scanner = scanner;
+    }
+
+    // Whether a failed read is worth a rescan
+    pub(crate) fn should_reconnect(&self, result: &Result<Vec<u8>, QrngError>) -> bool {
+        self.reconnect_attempts > 0
+            && matches!(result, Err(QrngError::DeviceDisconnected | QrngError::CommunicationError(_)))
+    }
+
+    /// Mark `serial` uninitialized, then rescan until it reappears or the
+    /// attempts run out. The device keeps its configuration; only the USB
+    /// handle is replaced, for every clone at once.
+    pub(crate) async fn reconnect(&self, serial: &str) -> Result<QrngDevice, QrngError> {
+        let mut device = self.get_device(serial).await?;
+        device.shutdown().await;
+        self.add_device(device.clone()).await?;
+
+        for attempt in 1..=self.reconnect_attempts {
+            if attempt > 1 {
+                tokio::time::sleep(RECONNECT_INTERVAL).await;
+            }
+            let found = match (self.scanner)().await {
+                Ok(found) => found,
+                Err(e) => {
+                    warn!("Rescan {} of {} for {} failed: {}", attempt, self.reconnect_attempts, serial, e);
+                    continue;
+                }
+            };
+            for candidate in found {
+                if candidate.serial().await.is_ok_and(|found| found == serial) {
+                    device.adopt(candidate).await;
+                    self.initialize_cooperatively(&mut device).await?;
+                    self.add_device(device.clone()).await?;
+                    info!("Reconnected device {}", serial);
+                    return Ok(device);
+                }
+            }
+        }
+        warn!("Device {} did not reappear after {} rescan(s)", serial, self.reconnect_attempts);
+        Err(QrngError::DeviceDisconnected)
+    }
+}
+
+impl QrngDevice {
+    // Take over `other`'s USB handle and bus location
+    async fn adopt(&mut self, other: QrngDevice) {
+        // Freshly scanned devices have no clones sharing the handle
+        let Ok(transport) = Arc::try_unwrap(other.transport) else {
+            return;
+        };
+        *self.transport.lock().await = transport.into_inner();
+        self.usb_version = other.usb_version;
+        self.device_version = other.device_version;
+        self.bus_number = other.bus_number;
+        self.address = other.address;
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+use tracing::{info, warn};
+use crate::error::QrngError;
+use super::{QrngDevice, DEFAULT_RETRY_BASE_DELAY, MAX_RETRY_DELAY};
+
+/// Classes of read failure a `RetryPolicy` can act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryableError {
+    /// `QrngError::Timeout`.
+    Timeout,
+    /// `QrngError::CommunicationError`, e.g. `LIBUSB_ERROR_IO` on a noisy hub.
+    Communication,
+    /// `QrngError::DeviceDisconnected`. Only worth retrying by reopening
+    /// the device, in case it has come back.
+    Disconnected,
+}
+
+impl RetryableError {
+    fn of(error: &QrngError) -> Option<Self> {
+        match error {
+            QrngError::Timeout { .. } => Some(RetryableError::Timeout),
+            QrngError::CommunicationError(_) => Some(RetryableError::Communication),
+            QrngError::DeviceDisconnected => Some(RetryableError::Disconnected),
+            _ => None,
+        }
+    }
+}
+
+/// How `QrngDevice::read_entropy_retry` handles failed reads.
+///
+/// Timeouts and communication errors are always retried. Errors listed in
+/// `reinitialize_on` are retried too, after closing and reopening the
+/// device. Anything else, such as an uninitialized device or a failed
+/// health test, is returned at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first.
+    pub max_attempts: usize,
+    /// Delay before the first retry; each further retry waits twice as
+    /// long, up to `MAX_RETRY_DELAY` or `backoff` if that is longer.
+    pub backoff: Duration,
+    pub reinitialize_on: Vec<RetryableError>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: DEFAULT_RETRY_BASE_DELAY,
+            reinitialize_on: vec![RetryableError::Communication],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the retry after one that waited `delay`.
+    pub(crate) fn next_delay(&self, delay: Duration) -> Duration {
+        delay.saturating_mul(2).min(MAX_RETRY_DELAY.max(self.backoff))
+    }
+}
+
+impl QrngDevice {
+    /// `read_entropy`, retrying failures as set out by `policy` with
+    /// exponential backoff. Returns the last error if every attempt fails.
+    pub async fn read_entropy_retry(&self, size: usize, policy: &RetryPolicy) -> Result<Vec<u8>, QrngError> {
+        let mut delay = policy.backoff;
+        let mut attempt = 1;
+        loop {
+            let error = match self.read_entropy(size).await {
+                Ok(entropy) => return Ok(entropy),
+                Err(e) => e,
+            };
+            let reinitialize = match RetryableError::of(&error) {
+                Some(kind) if policy.reinitialize_on.contains(&kind) => true,
+                Some(RetryableError::Timeout | RetryableError::Communication) => false,
+                _ => return Err(error),
+            };
+            if attempt >= policy.max_attempts {
+                return Err(error);
+            }
+
+            warn!("Read attempt {} of {} failed, retrying in {:?}: {}", attempt, policy.max_attempts, delay, error);
+            tokio::time::sleep(delay).await;
+            if reinitialize {
+                self.reopen().await?;
+            }
+            delay = policy.next_delay(delay);
+            attempt += 1;
+        }
+    }
+
+    // Close and reopen the USB handle in place, keeping the configuration
+    // settled by the first initialize and reapplying its FTDI settings
+    async fn reopen(&self) -> Result<(), QrngError> {
+        let mut transport = self.transport.lock().await;
+        transport.close();
+        self.run_init_sequence(transport.as_mut())?;
+        self.configure_ftdi_locked(transport.as_mut())?;
+        info!("Reopened QRNG device after a failed read");
+        Ok(())
+    }
+}
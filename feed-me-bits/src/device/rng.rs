@@ -0,0 +1,43 @@
+use rand_core::{CryptoRng, RngCore};
+use super::QrngDevice;
+use crate::error::QrngError;
+
+/// Draws every value straight from the device. `fill_bytes` and the
+/// `next_*` methods panic if the device fails; use `try_fill_bytes` to
+/// handle errors.
+impl RngCore for QrngDevice {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_bytes(dest) {
+            panic!("Failed to read entropy from QRNG device: {}", e);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        // The device may deliver less than asked for, so keep reading
+        let mut filled = 0;
+        while filled < dest.len() {
+            let entropy = self.read_entropy_blocking(dest.len() - filled).map_err(rand_core::Error::new)?;
+            if entropy.is_empty() {
+                let e = QrngError::CommunicationError("Device returned no data".to_string());
+                return Err(rand_core::Error::new(e));
+            }
+            dest[filled..filled + entropy.len()].copy_from_slice(&entropy);
+            filled += entropy.len();
+        }
+        Ok(())
+    }
+}
+
+impl CryptoRng for QrngDevice {}
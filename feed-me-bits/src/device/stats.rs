@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use crate::error::QrngError;
+use super::DeviceManager;
+
+/// Running totals of reads through `DeviceManager::read_entropy` for one
+/// device, for capacity planning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeviceStats {
+    /// Bytes delivered by successful reads.
+    pub bytes_read: u64,
+    /// Successful reads.
+    pub read_count: u64,
+    /// Failed reads, including those rejected by the continuous test.
+    pub error_count: u64,
+    /// When the last successful read finished.
+    pub last_read: Option<Instant>,
+}
+
+impl DeviceStats {
+    pub(crate) fn record(&mut self, result: &Result<Vec<u8>, QrngError>, now: Instant) {
+        match result {
+            Ok(entropy) => {
+                self.bytes_read += entropy.len() as u64;
+                self.read_count += 1;
+                self.last_read = Some(now);
+            }
+            Err(_) => self.error_count += 1,
+        }
+    }
+}
+
+impl DeviceManager {
+    /// Read totals for `serial`, all zero if it hasn't been read from yet.
+    pub async fn stats(&self, serial: &str) -> Result<DeviceStats, QrngError> {
+        self.get_device(serial).await?;
+        let stats = self.stats.lock().await;
+        Ok(stats.get(serial).copied().unwrap_or_default())
+    }
+
+    /// Read totals for every managed device, keyed by serial.
+    pub async fn all_stats(&self) -> HashMap<String, DeviceStats> {
+        let serials = self.list_devices().await;
+        let stats = self.stats.lock().await;
+        serials.into_iter()
+            .map(|serial| {
+                let device_stats = stats.get(&serial).copied().unwrap_or_default();
+                (serial, device_stats)
+            })
+            .collect()
+    }
+}
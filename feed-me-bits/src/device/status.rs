@@ -0,0 +1,88 @@
+use crate::error::QrngError;
+use super::DeviceStatus;
+
+/// Header byte every status frame starts with.
+pub const STATUS_MAGIC: u8 = 0xa5;
+
+/// Offset of the little-endian `i16` temperature field.
+pub const STATUS_TEMPERATURE_OFFSET: usize = 1;
+
+/// Temperature field units per degree Celsius.
+pub const STATUS_TEMPERATURE_SCALE: f32 = 100.0;
+
+/// Offset of the little-endian `u16` supply voltage field.
+pub const STATUS_VOLTAGE_OFFSET: usize = 3;
+
+/// Voltage field units per volt.
+pub const STATUS_VOLTAGE_SCALE: f32 = 1000.0;
+
+/// Length of the status frame read from the status endpoint, without the
+/// checksum.
+pub const STATUS_FRAME_LEN: usize = 5;
+
+/// Offset of the checksum byte: the XOR of every byte before it.
+pub const STATUS_CHECKSUM_OFFSET: usize = STATUS_FRAME_LEN;
+
+/// The frame returned by the status endpoint. With the default constants:
+///
+/// | Bytes | Field       | Encoding                                  |
+/// |-------|-------------|-------------------------------------------|
+/// | 0     | magic       | `STATUS_MAGIC`                            |
+/// | 1..3  | temperature | little-endian `i16`, hundredths of a °C   |
+/// | 3..5  | voltage     | little-endian `u16`, supply in millivolts |
+/// | 5     | checksum    | XOR of bytes 0..5                         |
+///
+/// The firmware's layout isn't published, so every offset and scale is a
+/// constant above and can be corrected without touching `status()`. Older
+/// firmware sends no checksum; see `DeviceConfig::verify_checksum`. Bytes
+/// past the end of the frame are ignored.
+#[derive(Debug, Clone, Copy)]
+pub struct StatusFrame;
+
+impl StatusFrame {
+    /// Decode a frame. Frames only come from an open device, so the result
+    /// is marked initialized.
+    pub fn parse(raw: &[u8]) -> Result<DeviceStatus, QrngError> {
+        if raw.len() < STATUS_FRAME_LEN {
+            return Err(QrngError::ProtocolError(format!(
+                "Status frame too short: got {} of {} bytes", raw.len(), STATUS_FRAME_LEN
+            )));
+        }
+        if raw[0] != STATUS_MAGIC {
+            return Err(QrngError::ProtocolError(format!(
+                "Status frame has magic {:#04x}, expected {:#04x}", raw[0], STATUS_MAGIC
+            )));
+        }
+
+        let t = STATUS_TEMPERATURE_OFFSET;
+        let v = STATUS_VOLTAGE_OFFSET;
+        let temperature = i16::from_le_bytes([raw[t], raw[t + 1]]);
+        let voltage = u16::from_le_bytes([raw[v], raw[v + 1]]);
+        Ok(DeviceStatus {
+            initialized: true,
+            temperature: temperature as f32 / STATUS_TEMPERATURE_SCALE,
+            voltage: voltage as f32 / STATUS_VOLTAGE_SCALE,
+        })
+    }
+
+    /// `parse`, after checking the trailing checksum byte.
+    pub fn parse_checked(raw: &[u8]) -> Result<DeviceStatus, QrngError> {
+        let Some(&checksum) = raw.get(STATUS_CHECKSUM_OFFSET) else {
+            return Err(QrngError::ProtocolError(format!(
+                "Status frame too short: got {} of {} bytes", raw.len(), STATUS_CHECKSUM_OFFSET + 1
+            )));
+        };
+        let expected = Self::checksum(&raw[..STATUS_CHECKSUM_OFFSET]);
+        if checksum != expected {
+            return Err(QrngError::ProtocolError(format!(
+                "Status checksum mismatch: got {:#04x}, expected {:#04x}", checksum, expected
+            )));
+        }
+        Self::parse(raw)
+    }
+
+    /// The checksum byte the firmware appends to `frame`.
+    pub fn checksum(frame: &[u8]) -> u8 {
+        frame.iter().fold(0, |acc, byte| acc ^ byte)
+    }
+}
@@ -1,7 +1,10 @@
 #[cfg(test)]
 use super::*;
+use super::mock::{MockQrngDevice, MockTransport, UsbCommand};
+use super::hotplug::HotplugEvent;
+use crate::{FTDI_VENDOR_ID, FTDI_PRODUCT_ID};
+use crate::source::{EntropySource, FallbackSource};
 use tokio_test::block_on;
-use tracing_subscriber::FmtSubscriber;
 
 #[tokio::test]
 async fn test_device_manager() {
@@ -99,18 +102,1548 @@ async fn test_device_manager_error_handling() {
     assert!(matches!(result.unwrap_err(), QrngError::DeviceNotFound(_)));
 }
 
+#[tokio::test]
+async fn test_disconnect_mid_read_fails_over() {
+    let manager = DeviceManager::new();
+    let first = MockTransport::new("MOCK-A");
+    let second = MockTransport::new("MOCK-B");
+    first.push_read(Err(rusb::Error::NoDevice));
+
+    for mock in [&first, &second] {
+        let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+        manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    }
+
+    // MOCK-A is tried first, unplugs, and MOCK-B serves the retry
+    let entropy = manager.read_entropy_any(32).await.expect("Failed to read entropy");
+    assert_eq!(entropy.len(), 32);
+    assert_eq!(first.bulk_reads(), 1);
+    assert_eq!(second.bulk_reads(), 1);
+
+    // The unplugged device is gone
+    assert_eq!(manager.list_devices().await, vec!["MOCK-B".to_string()]);
+    let (serial, _) = manager.read_entropy_from_any(32).await.expect("Failed to read entropy");
+    assert_eq!(serial, "MOCK-B");
+}
+
+#[tokio::test]
+async fn test_read_any_skips_failing_devices() {
+    let mut manager = DeviceManager::new();
+    // MOCK-A is never initialized, MOCK-B fails its health tests
+    manager.add_device(MockTransport::new("MOCK-A").device()).await.expect("Failed to add device");
+    let mut unhealthy = MockTransport::new("MOCK-B").device();
+    unhealthy.initialize().await.expect("Failed to initialize device");
+    unhealthy.set_health_monitor(HealthMonitor::new(1, 512, 512));
+    manager.add_device(unhealthy).await.expect("Failed to add device");
+
+    // With nothing healthy, the last device's error
+    let result = manager.read_entropy_any(32).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+
+    // MOCK-C serves the read despite the two before it
+    let serial = manager.add_device(MockTransport::new("MOCK-C").device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    let (serial, entropy) = manager.read_entropy_from_any(32).await.expect("Failed to read entropy");
+    assert_eq!((serial.as_str(), entropy.len()), ("MOCK-C", 32));
+
+    // And the fallback is tried when every device fails
+    manager.remove_device("MOCK-C").await.expect("Failed to remove device");
+    manager.set_fallback(Arc::new(FallbackSource));
+    let (serial, _) = manager.read_entropy_from_any(32).await.expect("Failed to read fallback entropy");
+    assert_eq!(serial, FALLBACK_SERIAL);
+}
+
+#[tokio::test]
+async fn test_disconnect_removes_device() {
+    let manager = DeviceManager::new();
+    let mock = MockTransport::new("MOCK-A");
+    mock.push_read(Ok(vec![0x5a; 16]));
+    mock.push_read(Err(rusb::Error::NoDevice));
+
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    let entropy = manager.read_entropy(&serial, 16).await.expect("Failed to read entropy");
+    assert_eq!(entropy, vec![0x5a; 16]);
+
+    let result = manager.read_entropy(&serial, 16).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceDisconnected));
+    assert!(manager.list_devices().await.is_empty());
+    assert!(manager.quarantined_devices().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_status_disconnect_removes_device() {
+    let manager = DeviceManager::new();
+    let mock = MockTransport::new("MOCK-A");
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    mock.push_read(Err(rusb::Error::NoDevice));
+    let result = manager.get_device_status(&serial).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceDisconnected));
+    assert!(manager.list_devices().await.is_empty());
+
+    let result = manager.get_device_status(&serial).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceNotFound(_)));
+}
+
+#[tokio::test]
+async fn test_reconnect_after_disconnect() {
+    let mut manager = DeviceManager::new();
+    manager.set_reconnect_attempts(1);
+    let unplugged = MockTransport::new("MOCK-A");
+    let replugged = MockTransport::new("MOCK-A");
+    let bus = Arc::new(std::sync::Mutex::new(vec![replugged.clone()]));
+    let scanned = bus.clone();
+    manager.set_scanner(Arc::new(move || {
+        let found = scanned.lock().unwrap().iter().map(MockTransport::device).collect();
+        Box::pin(async move { Ok(found) })
+    }));
+
+    let serial = manager.add_device(unplugged.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    // The read is retried on the device found by the rescan
+    unplugged.push_read(Err(rusb::Error::NoDevice));
+    replugged.push_read(Ok(vec![0x5a; 16]));
+    let entropy = manager.read_entropy(&serial, 16).await.expect("Failed to read entropy");
+    assert_eq!(entropy, vec![0x5a; 16]);
+    assert!(!unplugged.is_open());
+    assert!(replugged.is_open());
+    assert_eq!(manager.list_devices().await, vec![serial.clone()]);
+
+    // A device that stays away is removed as before
+    bus.lock().unwrap().clear();
+    replugged.push_read(Err(rusb::Error::NoDevice));
+    let result = manager.read_entropy(&serial, 16).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceDisconnected));
+    assert!(manager.list_devices().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_disconnect_quarantines_device() {
+    let manager = DeviceManager::with_disconnect_strategy(DisconnectStrategy::Quarantine);
+    let mock = MockTransport::new("MOCK-A");
+    mock.push_read(Err(rusb::Error::NoDevice));
+
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    let result = manager.read_entropy(&serial, 16).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceDisconnected));
+    assert!(manager.list_devices().await.is_empty());
+    assert_eq!(manager.quarantined_devices().await, vec![serial]);
+}
+
+#[tokio::test]
+async fn test_continuous_test_rejects_repeated_block() {
+    let mut manager = DeviceManager::new();
+    manager.set_continuous_test(true);
+    let mock = MockTransport::new("MOCK-A");
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    manager.read_entropy(&serial, 64).await.expect("Failed to read entropy");
+
+    // The check carries across reads: a read starting with the block that
+    // ended the previous one fails
+    mock.push_read(Ok(vec![0x11, 0x22, 0x33, 0x44]));
+    manager.read_entropy(&serial, 4).await.expect("Failed to read entropy");
+    mock.push_read(Ok(vec![0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88]));
+    let result = manager.read_entropy(&serial, 8).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+
+    // Pooled and balanced reads are checked too
+    mock.push_read(Ok(vec![0x55, 0x66, 0x77, 0x88]));
+    manager.read_entropy(&serial, 4).await.expect("Failed to read entropy");
+    mock.push_read(Ok(vec![0x55, 0x66, 0x77, 0x88]));
+    let result = manager.read_entropy_pooled(4).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+    mock.push_read(Ok(vec![0x99, 0xaa, 0xbb, 0xcc, 0x99, 0xaa, 0xbb, 0xcc]));
+    let result = manager.read_entropy_balanced(8).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+
+    // Disabled by default
+    let manager = DeviceManager::new();
+    let mock = MockTransport::new("MOCK-B");
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    mock.push_read(Ok(vec![0x00; 8]));
+    manager.read_entropy(&serial, 8).await.expect("Failed to read entropy");
+}
+
+#[tokio::test]
+async fn test_relinquish_releases_and_reclaims() {
+    let window = Duration::from_millis(20);
+    let mut manager = DeviceManager::new();
+    let mock = MockTransport::new("MOCK-A");
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    let result = manager.relinquish(&serial).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+
+    // The other process still holds the interface after the first window
+    manager.set_relinquish_window(Some(window));
+    mock.set_busy_claims(1);
+    let start = std::time::Instant::now();
+    let relinquish = tokio::spawn({
+        let manager = manager.clone();
+        let serial = serial.clone();
+        async move { manager.relinquish(&serial).await }
+    });
+    tokio::time::sleep(Duration::from_millis(5)).await;
+
+    // Reads wait for the interface to come back
+    manager.read_entropy(&serial, 16).await.expect("Failed to read entropy");
+    assert!(start.elapsed() >= window * 2);
+    relinquish.await.unwrap().expect("Failed to relinquish");
+
+    let commands = mock.commands();
+    assert_eq!(&commands[commands.len() - 3..], [
+        UsbCommand::ReleaseInterface(0),
+        UsbCommand::ClaimInterface(0),
+        UsbCommand::ClaimInterface(0),
+    ]);
+
+    // Gives up once the other process has held it for every attempt
+    mock.set_busy_claims(RECLAIM_ATTEMPTS);
+    let result = manager.relinquish(&serial).await;
+    assert!(matches!(result.unwrap_err(), QrngError::UsbError(rusb::Error::Busy)));
+}
+
+#[tokio::test]
+async fn test_initialize_waits_out_busy_interface() {
+    let mock = MockTransport::new("MOCK-A");
+    mock.set_busy_claims(1);
+
+    // Without cooperative mode a busy interface fails initialization
+    let manager = DeviceManager::new();
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    let result = manager.initialize_device(&serial).await;
+    assert!(matches!(result.unwrap_err(), QrngError::UsbError(rusb::Error::Busy)));
+
+    let mut manager = DeviceManager::new();
+    manager.set_relinquish_window(Some(Duration::from_millis(10)));
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    mock.set_busy_claims(1);
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    assert!(manager.get_device(&serial).await.unwrap().is_initialized());
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn test_inventory() {
+    let mut manager = DeviceManager::with_disconnect_strategy(DisconnectStrategy::Quarantine);
+    manager.set_error_rate_alarm(ErrorRateAlarm { min_reads: 1, ..ErrorRateAlarm::default() });
+
+    let mut checked = MockTransport::new("MOCK-A").device();
+    checked.set_health_monitor(HealthMonitor::for_min_entropy(8.0));
+    checked.set_read_timeout(Duration::from_millis(250));
+    manager.add_device(checked).await.expect("Failed to add device");
+    manager.initialize_device("MOCK-A").await.expect("Failed to initialize device");
+
+    // MOCK-B fails its only read, MOCK-C is unplugged, MOCK-D is never initialized
+    let failing = MockTransport::new("MOCK-B");
+    let unplugged = MockTransport::new("MOCK-C");
+    for mock in [&failing, &unplugged] {
+        let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+        manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    }
+    failing.push_read(Err(rusb::Error::Io));
+    unplugged.push_read(Err(rusb::Error::NoDevice));
+    let _ = manager.read_entropy("MOCK-B", 8).await;
+    let _ = manager.read_entropy("MOCK-C", 8).await;
+    let dual = MockTransport::new("MOCK-D");
+    dual.set_device_version(0x0700);
+    manager.add_device(dual.device().with_interface(1)).await.expect("Failed to add device");
+
+    let json = serde_json::to_value(manager.inventory().await).expect("Failed to serialize inventory");
+    let devices = json["devices"].as_array().expect("Missing devices array");
+    let serials: Vec<_> = devices.iter().map(|d| d["serial"].as_str().unwrap()).collect();
+    assert_eq!(serials, ["MOCK-A", "MOCK-B", "MOCK-C", "MOCK-D"]);
+    let health: Vec<_> = devices.iter().map(|d| d["health"].as_str().unwrap()).collect();
+    assert_eq!(health, ["healthy", "degraded", "quarantined", "healthy"]);
+
+    let first = &devices[0];
+    assert_eq!(first["manufacturer"], "FTDI");
+    assert_eq!(first["description"], "Mock QRNG");
+    assert_eq!(first["vendor_id"], 0x0403);
+    assert_eq!(first["product_id"], 0x6001);
+    assert_eq!(first["initialized"], true);
+    assert_eq!(first["error_rate"], 0.0);
+    assert_eq!(first["firmware"], "6.0");
+    assert_eq!(first["chip"], "FT232R");
+    assert_eq!(first["channels"], 1);
+    assert_eq!(first["config"]["read_timeout_ms"], 250);
+    assert_eq!(first["config"]["health_tests"], true);
+    assert_eq!(first["config"]["interface"], 0);
+    assert_eq!(first["config"]["entropy_endpoint"], 0x81);
+    assert_eq!(first["config"]["status_endpoint"], DEFAULT_STATUS_ENDPOINT);
+    assert_eq!(first["config"]["max_transfer"], DEFAULT_MAX_TRANSFER);
+    assert_eq!(first["config"]["verify_checksum"], true);
+    assert_eq!(first["config"]["bitmode"], serde_json::Value::Null);
+    assert_eq!(devices[1]["error_rate"], 1.0);
+    assert_eq!(devices[3]["initialized"], false);
+    assert_eq!(devices[3]["firmware"], "7.0");
+    assert_eq!(devices[3]["chip"], "FT2232H");
+    assert_eq!(devices[3]["channels"], 2);
+    assert_eq!(devices[3]["config"]["interface"], 1);
+    assert_eq!(devices[3]["config"]["entropy_endpoint"], 0x83);
+    for device in devices {
+        let fields = device.as_object().unwrap();
+        assert_eq!(fields.len(), 12, "unexpected fields in {:?}", fields.keys());
+        let config = device["config"].as_object().unwrap();
+        assert_eq!(config.len(), 10, "unexpected config fields in {:?}", config.keys());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn test_device_info_round_trip() {
+    let device = MockTransport::new("MOCK-A").device();
+    let info = device.info().await.expect("Failed to read device info");
+    assert_eq!(info, DeviceInfo {
+        serial: "MOCK-A".to_string(),
+        vendor_id: FTDI_VENDOR_ID,
+        product_id: FTDI_PRODUCT_ID,
+        manufacturer: Some("FTDI".to_string()),
+        description: Some("Mock QRNG".to_string()),
+        usb_version: "2.0".to_string(),
+        bus_number: 1,
+        address: 4,
+        initialized: false,
+    });
+
+    let json = serde_json::to_string(&info).expect("Failed to serialize device info");
+    assert!(json.contains(r#""serial":"MOCK-A""#), "{}", json);
+    assert!(json.contains(r#""vendor_id":"0x0403","product_id":"0x6001""#), "{}", json);
+    let decoded: DeviceInfo = serde_json::from_str(&json).expect("Failed to deserialize device info");
+    assert_eq!(decoded, info);
+
+    let status = DeviceStatus { initialized: true, temperature: 36.5, voltage: 4.75 };
+    let json = serde_json::to_string(&status).expect("Failed to serialize status");
+    assert_eq!(serde_json::from_str::<DeviceStatus>(&json).unwrap(), status);
+}
+
+#[tokio::test]
+async fn test_firmware_version() {
+    let mock = MockTransport::new("MOCK-A");
+    mock.set_device_version(0x1025);
+    let mut device = mock.device();
+    assert_eq!(device.firmware_version(None).await.unwrap(), "10.2");
+
+    // The vendor query reads the revision string over a control transfer
+    let query = FirmwareQuery { request_type: 0xc0, request: 0x90, value: 0, index: 0, length: 16 };
+    device.initialize().await.expect("Failed to initialize device");
+    mock.push_read(Ok(b"2.4.1\0\0".to_vec()));
+    assert_eq!(device.firmware_version(Some(&query)).await.unwrap(), "2.4.1");
+    assert_eq!(mock.commands().last(), Some(&UsbCommand::ControlIn {
+        request_type: 0xc0, request: 0x90, value: 0, index: 0, len: 16,
+    }));
+
+    // Devices without the request stall it
+    let result = device.firmware_version(Some(&query)).await;
+    assert!(matches!(result.unwrap_err(), QrngError::UsbError(rusb::Error::Pipe)));
+}
+
+#[tokio::test]
+async fn test_flush_before_read() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+    let purge = |value| UsbCommand::Control {
+        request_type: FTDI_OUT_REQUEST_TYPE, request: SIO_RESET_REQUEST, value, index: 1, data: Vec::new(),
+    };
+
+    device.flush().await.expect("Failed to flush device");
+    assert_eq!(mock.commands()[mock.commands().len() - 2..], [purge(SIO_RESET_PURGE_RX), purge(SIO_RESET_PURGE_TX)]);
+
+    // Off by default, reads go straight to the bulk endpoint
+    let issued = mock.commands().len();
+    device.read_entropy(16).await.unwrap();
+    assert!(!mock.commands()[issued..].iter().any(|command| matches!(command, UsbCommand::Control { .. })));
+
+    device.set_flush_before_read(true);
+    let issued = mock.commands().len();
+    device.read_entropy(16).await.unwrap();
+    assert_eq!(mock.commands()[issued..issued + 2], [purge(SIO_RESET_PURGE_RX), purge(SIO_RESET_PURGE_TX)]);
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn test_device_info_tolerates_unreadable_strings() {
+    let mock = MockTransport::new("MOCK-A");
+    mock.set_strings_readable(false);
+    let info = mock.device().info().await.expect("Failed to read device info");
+    assert_eq!((info.manufacturer, info.description), (None, None));
+
+    let json = serde_json::to_value(mock.device().info().await.unwrap()).unwrap();
+    assert_eq!(json["serial"], "MOCK-A");
+    assert!(json["manufacturer"].is_null());
+}
+
+#[tokio::test]
+async fn test_hotplug_events_keep_manager_in_sync() {
+    let manager = DeviceManager::new();
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let watcher = tokio::spawn(manager.clone().apply_hotplug_events(rx));
+
+    // Two devices arrive, then the first one leaves
+    tx.send(HotplugEvent::Arrived { location: (1, 4), transport: Box::new(MockTransport::new("MOCK-A")) }).unwrap();
+    tx.send(HotplugEvent::Arrived { location: (1, 5), transport: Box::new(MockTransport::new("MOCK-B")) }).unwrap();
+    tx.send(HotplugEvent::Left { location: (1, 4) }).unwrap();
+
+    // Departures for unknown locations are ignored
+    tx.send(HotplugEvent::Left { location: (2, 9) }).unwrap();
+
+    drop(tx);
+    watcher.await.unwrap();
+
+    assert_eq!(manager.list_devices().await, vec!["MOCK-B".to_string()]);
+}
+
+#[tokio::test]
+async fn test_register_scanned_is_idempotent() {
+    let manager = DeviceManager::new();
+    let mock_a = MockTransport::new("MOCK-A");
+    let mock_b = MockTransport::new("MOCK-B");
+    let serials = manager.register_scanned(vec![mock_a.device(), mock_b.device()]).await
+        .expect("Failed to register devices");
+    assert_eq!(serials, vec!["MOCK-A".to_string(), "MOCK-B".to_string()]);
+    manager.initialize_device("MOCK-A").await.expect("Failed to initialize device");
+
+    // A second scan of the same bus hands back fresh, unopened handles
+    let rescanned = manager.register_scanned(vec![
+        MockTransport::new("MOCK-A").device(),
+        MockTransport::new("MOCK-B").device(),
+    ]).await.expect("Failed to register devices");
+    assert_eq!(rescanned, serials);
+    assert!(manager.get_device("MOCK-A").await.unwrap().is_initialized());
+    assert!(!manager.get_device("MOCK-B").await.unwrap().is_initialized());
+    assert!(mock_a.is_open());
+
+    // Unplugging one device removes and closes it, a new one is picked up
+    let serials = manager.register_scanned(vec![MockTransport::new("MOCK-C").device(), mock_b.device()]).await
+        .expect("Failed to register devices");
+    assert_eq!(serials, vec!["MOCK-B".to_string(), "MOCK-C".to_string()]);
+    assert!(!mock_a.is_open());
+}
+
+#[test]
+fn test_device_filter_ids() {
+    let filter = DeviceFilter::default();
+    assert!(filter.matches_ids(0x0403, 0x6001));
+    assert!(!filter.matches_ids(0x0403, 0x6014));
+    assert!(!filter.matches_ids(0x1234, 0x6001));
+
+    // No product IDs accepts everything from the listed vendors
+    let filter = DeviceFilter { product_ids: Vec::new(), ..DeviceFilter::default() };
+    assert!(filter.matches_ids(0x0403, 0x6014));
+    assert!(!filter.matches_ids(0x1234, 0x6014));
+
+    // Several models at once, still only under the FTDI vendor ID
+    let filter = DeviceFilter::with_product_ids(&[0x6001, 0x6014]);
+    let descriptors = [(0x0403, 0x6001), (0x0403, 0x6014), (0x0403, 0x6010), (0x1234, 0x6014)];
+    let matched: Vec<_> = descriptors.iter().filter(|&&(vid, pid)| filter.matches_ids(vid, pid)).collect();
+    assert_eq!(matched, vec![&(0x0403, 0x6001), &(0x0403, 0x6014)]);
+}
+
+#[tokio::test]
+async fn test_device_filter_serial_allowlist() {
+    let devices = || vec![MockTransport::new("MOCK-A").device(), MockTransport::new("MOCK-B").device()];
+
+    let filter = DeviceFilter::default();
+    assert_eq!(filter.retain_allowed(devices()).await.len(), 2);
+
+    let filter = DeviceFilter { serial_allowlist: Some(vec!["MOCK-B".to_string()]), ..DeviceFilter::default() };
+    let allowed = filter.retain_allowed(devices()).await;
+    assert_eq!(allowed.len(), 1);
+    assert_eq!(allowed[0].serial().await.unwrap(), "MOCK-B");
+}
+
+#[test]
+fn test_stop_hotplug_watch_without_start() {
+    let manager = DeviceManager::new();
+    let result = manager.stop_hotplug_watch();
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+}
+
+#[tokio::test]
+async fn test_min_entropy_report() {
+    let manager = DeviceManager::new();
+    let mock = MockTransport::new("MOCK-A");
+    let biased: Vec<u8> = (0..4096u32).map(|i| if i % 3 == 0 { i as u8 } else { 0xff }).collect();
+    mock.push_read(Ok(biased));
+
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    let report = manager.min_entropy_report(&serial, 4096).await.expect("Failed to estimate min-entropy");
+    assert_eq!(report.sample_size, 4096);
+
+    // The biased source scores low, and the reported value is the smallest estimate
+    assert!(report.min_entropy < 1.0, "biased source scored {}", report.min_entropy);
+    let lowest = report.estimates.iter().map(|e| e.min_entropy).fold(f64::INFINITY, f64::min);
+    assert_eq!(report.min_entropy, lowest);
+}
+
+#[tokio::test]
+async fn test_measure_quality() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    let report = device.measure_quality(64 * 1024).await.expect("Failed to measure quality");
+    assert_eq!(report.sample_size, 64 * 1024);
+    assert!(report.shannon_entropy > 7.9, "mock scored {}", report.shannon_entropy);
+
+    mock.push_read(Ok(vec![0x00; 1024]));
+    let report = device.measure_quality(1024).await.expect("Failed to measure quality");
+    assert_eq!(report.shannon_entropy, 0.0);
+    assert_eq!(report.min_entropy, 0.0);
+}
+
+#[tokio::test]
+async fn test_sample_entropy_quality() {
+    let manager = DeviceManager::new();
+    let mock = MockTransport::new("MOCK-A");
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    let report = manager.sample_entropy_quality(&serial, 64 * 1024).await.expect("Failed to sample quality");
+    assert!(report.shannon_entropy > 7.9, "mock scored {}", report.shannon_entropy);
+    assert!(report.min_entropy > 7.0, "mock scored {}", report.min_entropy);
+
+    mock.push_read(Ok(vec![0xaa; 1024]));
+    let report = manager.sample_entropy_quality(&serial, 1024).await.expect("Failed to sample quality");
+    assert_eq!(report.shannon_entropy, 0.0);
+    assert_eq!(report.min_entropy, 0.0);
+    assert_eq!(manager.stats(&serial).await.unwrap().read_count, 2);
+
+    let result = manager.sample_entropy_quality("NOPE", 16).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceNotFound(_)));
+}
+
+#[derive(Debug)]
+struct VendorInitSequence;
+
+impl InitSequence for VendorInitSequence {
+    fn init(&self, handle: &mut dyn UsbHandle) -> Result<(), QrngError> {
+        handle.set_active_configuration(2)?;
+        handle.claim_interface(1)?;
+        handle.write_control(0x40, 0x0b, 0x01ff, 1, &[], Duration::from_millis(100))?;
+        handle.write_bulk(0x02, b"START", Duration::from_millis(100))?;
+        let mut ack = [0u8; 2];
+        handle.read_bulk(0x83, &mut ack, Duration::from_millis(100))?;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_default_init_sequence() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    assert_eq!(mock.commands(), vec![
+        UsbCommand::Reset,
+        UsbCommand::SetConfiguration(1),
+        UsbCommand::ClaimInterface(0),
+    ]);
+}
+
+#[tokio::test]
+async fn test_custom_init_sequence() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.set_init_sequence(Arc::new(VendorInitSequence));
+    device.initialize().await.expect("Failed to initialize device");
+
+    assert_eq!(mock.commands(), vec![
+        UsbCommand::SetConfiguration(2),
+        UsbCommand::ClaimInterface(1),
+        UsbCommand::Control { request_type: 0x40, request: 0x0b, value: 0x01ff, index: 1, data: vec![] },
+        UsbCommand::BulkOut { endpoint: 0x02, data: b"START".to_vec() },
+        UsbCommand::BulkIn { endpoint: 0x83, len: 2 },
+    ]);
+}
+
+#[test]
+fn test_clone_device_many_times() {
+    let device = MockTransport::new("MOCK-A").device();
+    let clones: Vec<QrngDevice> = (0..10_000).map(|_| device.clone()).collect();
+
+    for clone in &clones {
+        assert_eq!(clone.vendor_id(), FTDI_VENDOR_ID);
+        assert_eq!(clone.product_id(), FTDI_PRODUCT_ID);
+    }
+
+    // Clones share the transport, so dropping them leaves the original usable
+    drop(clones);
+    assert_eq!(block_on(device.serial()).expect("Failed to read serial"), "MOCK-A");
+}
+
+#[tokio::test]
+async fn test_handle_reused_across_reads() {
+    let manager = DeviceManager::new();
+    let mock = MockTransport::new("MOCK-A");
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    for _ in 0..100 {
+        manager.read_entropy(&serial, 64).await.expect("Failed to read entropy");
+    }
+    mock.push_read(Ok(status_frame(2500, 5000)));
+    manager.get_device_status(&serial).await.expect("Failed to get status");
+    assert_eq!(mock.opens(), 1);
+    assert_eq!(mock.bulk_reads(), 101);
+
+    // Removing the device closes the handle, including for clones
+    let device = manager.get_device(&serial).await.unwrap();
+    manager.remove_device(&serial).await.expect("Failed to remove device");
+    assert!(!mock.is_open());
+    assert_eq!(mock.commands().last(), Some(&UsbCommand::ReleaseInterface(0)));
+    let result = device.read_entropy(16).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceNotInitialized));
+}
+
+#[tokio::test]
+async fn test_startup_check() {
+    // A source stuck on zero is rejected and left closed
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.set_startup_check(true);
+    mock.push_read(Ok(vec![0; 256]));
+    let result = device.initialize().await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(msg) if msg == "startup entropy check failed"));
+    assert!(!device.is_initialized());
+    assert!(!mock.is_open());
+
+    // As is one cycling through only a handful of values
+    mock.push_read(Ok((0..=255u8).map(|b| b % 4).collect()));
+    assert!(device.initialize().await.is_err());
+
+    // Random bytes pass, taking one sample read
+    let mock = MockTransport::new("MOCK-B");
+    let mut device = mock.device();
+    device.set_startup_check(true);
+    device.initialize().await.expect("Failed to initialize device");
+    assert!(device.is_initialized());
+    assert_eq!(mock.bulk_reads(), 1);
+
+    // The sample size is configurable, down to a floor
+    device.set_startup_sample_size(4096);
+    device.initialize().await.expect("Failed to initialize device");
+    assert_eq!(mock.largest_read(), 4096);
+    device.set_startup_sample_size(1);
+    device.initialize().await.expect("Failed to initialize device");
+    assert_eq!(mock.bulk_reads(), 3);
+    mock.push_read(Ok(vec![0; health::STARTUP_MIN_DISTINCT]));
+    assert!(device.initialize().await.is_err());
+}
+
+#[tokio::test]
+async fn test_shutdown_releases_interface() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+    device.shutdown().await;
+    assert!(!device.is_initialized());
+    assert!(!mock.is_open());
+    assert_eq!(mock.commands().last(), Some(&UsbCommand::ReleaseInterface(0)));
+
+    // The manager shuts down every device but keeps them registered
+    let manager = DeviceManager::new();
+    let mocks = [MockTransport::new("MOCK-A"), MockTransport::new("MOCK-B")];
+    for mock in &mocks {
+        let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+        manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    }
+    manager.shutdown_all().await;
+    for mock in &mocks {
+        assert!(!mock.is_open());
+        let serial = mock.device().serial().await.unwrap();
+        assert!(!manager.get_device(&serial).await.unwrap().is_initialized());
+    }
+}
+
+// A well-formed, checksummed status frame for the given raw field values
+fn status_frame(temperature: i16, voltage: u16) -> Vec<u8> {
+    let mut frame = vec![STATUS_MAGIC];
+    frame.extend_from_slice(&temperature.to_le_bytes());
+    frame.extend_from_slice(&voltage.to_le_bytes());
+    frame.push(StatusFrame::checksum(&frame));
+    frame
+}
+
+#[test]
+fn test_parse_status() {
+    // 36.50 °C, 4.750 V
+    let status = StatusFrame::parse(&[0xa5, 0x42, 0x0e, 0x8e, 0x12]).expect("Failed to parse status");
+    assert_eq!(status.temperature, 36.5);
+    assert_eq!(status.voltage, 4.75);
+
+    // -5.25 °C, 3.300 V, with a trailing byte ignored
+    let status = StatusFrame::parse(&[0xa5, 0xf3, 0xfd, 0xe4, 0x0c, 0xff]).expect("Failed to parse status");
+    assert_eq!(status.temperature, -5.25);
+    assert_eq!(status.voltage, 3.3);
+
+    // Short frames and a wrong header are both rejected
+    let result = StatusFrame::parse(&[0xa5, 0x42, 0x0e, 0x8e]);
+    assert!(matches!(result.unwrap_err(), QrngError::ProtocolError(_)));
+    let result = StatusFrame::parse(&[0x42, 0x0e, 0x8e, 0x12, 0x00]);
+    assert!(matches!(result.unwrap_err(), QrngError::ProtocolError(msg) if msg.contains("magic")));
+}
+
+#[tokio::test]
+async fn test_status_decodes_frame() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    mock.push_read(Ok(status_frame(3650, 4750)));
+    let status = device.status().await.expect("Failed to read status");
+    assert!(status.initialized);
+    assert_eq!((status.temperature, status.voltage), (36.5, 4.75));
+
+    // A short frame is a protocol error rather than garbage readings
+    mock.push_read(Ok(vec![STATUS_MAGIC, 0x42, 0x0e]));
+    let result = device.status().await;
+    assert!(matches!(result.unwrap_err(), QrngError::ProtocolError(_)));
+}
+
+#[tokio::test]
+async fn test_status_errors() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+
+    // Not zeroed readings before the handle is open
+    assert!(matches!(device.status().await.unwrap_err(), QrngError::DeviceNotInitialized));
+
+    device.initialize().await.expect("Failed to initialize device");
+    mock.push_read(Err(rusb::Error::Io));
+    assert!(matches!(device.status().await.unwrap_err(), QrngError::UsbError(rusb::Error::Io)));
+
+    // No zeroed readings without a status endpoint either
+    for stall in [rusb::Error::Pipe, rusb::Error::NotSupported] {
+        mock.push_read(Err(stall));
+        assert!(matches!(device.status().await.unwrap_err(), QrngError::StatusUnsupported));
+    }
+}
+
+#[tokio::test]
+async fn test_status_checksum() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    let mut corrupted = status_frame(3650, 4750);
+    corrupted[1] ^= 0x01;
+    mock.push_read(Ok(corrupted));
+    let result = device.status().await;
+    assert!(matches!(result.unwrap_err(), QrngError::ProtocolError(msg) if msg.contains("checksum")));
+
+    // A frame without a checksum is short unless verification is off
+    let unchecked = status_frame(3650, 4750)[..STATUS_FRAME_LEN].to_vec();
+    mock.push_read(Ok(unchecked.clone()));
+    assert!(matches!(device.status().await.unwrap_err(), QrngError::ProtocolError(_)));
+    let mut config = device.config();
+    config.verify_checksum = false;
+    device.set_config(config);
+    mock.push_read(Ok(unchecked));
+    let status = device.status().await.expect("Failed to read status");
+    assert_eq!((status.temperature, status.voltage), (36.5, 4.75));
+}
+
+#[tokio::test]
+async fn test_device_config() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.set_config(DeviceConfig {
+        config_value: 2,
+        interface: 1,
+        entropy_endpoint: 0x83,
+        status_endpoint: 0x84,
+        read_timeout: Duration::from_millis(250),
+        max_transfer: DEFAULT_MAX_TRANSFER,
+        verify_checksum: true,
+        latency_timer: None,
+        bitmode: None,
+    });
+    device.initialize().await.expect("Failed to initialize device");
+    assert_eq!(mock.commands(), vec![
+        UsbCommand::Reset,
+        UsbCommand::SetConfiguration(2),
+        UsbCommand::ClaimInterface(1),
+    ]);
+
+    device.read_entropy(16).await.expect("Failed to read entropy");
+    assert_eq!(mock.last_endpoint(), Some(0x83));
+    assert_eq!(mock.last_timeout(), Some(Duration::from_millis(250)));
+    mock.push_read(Ok(status_frame(2500, 5000)));
+    device.status().await.expect("Failed to read status");
+    assert_eq!(mock.last_endpoint(), Some(0x84));
+
+    // Closing releases the configured interface, not interface 0
+    device.close().await;
+    assert_eq!(mock.commands().last(), Some(&UsbCommand::ReleaseInterface(1)));
+}
+
+#[tokio::test]
+async fn test_custom_endpoints() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    // Defaults when the descriptors list no bulk IN endpoints
+    device.read_entropy(16).await.expect("Failed to read entropy");
+    assert_eq!(mock.last_endpoint(), Some(DEFAULT_ENTROPY_ENDPOINT));
+    mock.push_read(Ok(status_frame(2500, 5000)));
+    device.status().await.expect("Failed to read status");
+    assert_eq!(mock.last_endpoint(), Some(DEFAULT_STATUS_ENDPOINT));
+
+    // An explicit endpoint is honored by the read path
+    device.set_entropy_endpoint(0x83);
+    device.read_entropy(16).await.expect("Failed to read entropy");
+    assert_eq!(mock.last_endpoint(), Some(0x83));
+}
+
+#[tokio::test]
+async fn test_endpoints_inferred_from_descriptors() {
+    let mock = MockTransport::new("MOCK-A");
+    mock.set_bulk_in_endpoints(vec![0x83, 0x84]);
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+    assert_eq!((device.entropy_endpoint(), device.status_endpoint()), (0x83, 0x84));
+
+    // Explicit settings win over the descriptors
+    let mut device = mock.device();
+    device.set_status_endpoint(0x82);
+    device.initialize().await.expect("Failed to initialize device");
+    assert_eq!((device.entropy_endpoint(), device.status_endpoint()), (DEFAULT_ENTROPY_ENDPOINT, 0x82));
+}
+
+#[tokio::test]
+async fn test_with_interface() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device().with_interface(1);
+    device.initialize().await.expect("Failed to initialize device");
+    assert_eq!(mock.commands().last(), Some(&UsbCommand::ClaimInterface(1)));
+
+    // Without descriptors the channel's FTDI endpoint is used
+    device.read_entropy(16).await.expect("Failed to read entropy");
+    assert_eq!(mock.last_endpoint(), Some(0x83));
+
+    // Descriptors are read for the claimed interface only
+    mock.set_bulk_in_endpoints(vec![0x81, 0x82]);
+    mock.set_interface_endpoints(1, vec![0x85]);
+    let mut device = mock.device().with_interface(1);
+    device.initialize().await.expect("Failed to initialize device");
+    assert_eq!(device.entropy_endpoint(), 0x85);
+    assert_eq!(device.config().interface, 1);
+}
+
+#[tokio::test]
+async fn test_read_timeout() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    // Defaults to the historical 1000ms
+    assert_eq!(device.read_timeout(), DEFAULT_READ_TIMEOUT);
+    device.read_entropy(16).await.expect("Failed to read entropy");
+    assert_eq!(mock.last_timeout(), Some(Duration::from_millis(1000)));
+
+    // A short timeout against a silent endpoint fails fast
+    device.set_read_timeout(Duration::from_millis(5));
+    mock.set_responding(false);
+    let result = device.read_entropy(16).await;
+    let err = result.unwrap_err();
+    assert!(matches!(err, QrngError::Timeout { requested: 16, elapsed, .. } if elapsed >= Duration::from_millis(5)), "{:?}", err);
+    assert_eq!(mock.last_timeout(), Some(Duration::from_millis(5)));
+
+    // A per-call timeout overrides the configured one
+    let result = device.read_entropy_with_timeout(16, Duration::from_millis(20)).await;
+    let err = result.unwrap_err();
+    assert!(matches!(err, QrngError::Timeout { requested: 16, elapsed, .. } if elapsed >= Duration::from_millis(20)), "{:?}", err);
+    assert_eq!(mock.last_timeout(), Some(Duration::from_millis(20)));
+    assert_eq!(device.read_timeout(), Duration::from_millis(5));
+
+    // Status reads time out the same way
+    let result = device.status().await;
+    assert!(matches!(result.unwrap_err(), QrngError::Timeout { requested, .. } if requested == STATUS_CHECKSUM_OFFSET + 1));
+
+    // Other USB failures are still communication errors
+    mock.set_responding(true);
+    mock.push_read(Err(rusb::Error::Pipe));
+    let result = device.read_entropy(16).await;
+    assert!(matches!(result.unwrap_err(), QrngError::CommunicationError(_)));
+}
+
+#[tokio::test]
+async fn test_read_entropy_retry() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    let policy = |max_attempts| RetryPolicy {
+        max_attempts,
+        backoff: Duration::from_millis(5),
+        reinitialize_on: Vec::new(),
+    };
+
+    // Not retried: the device was never initialized
+    let result = device.read_entropy_retry(16, &policy(5)).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceNotInitialized));
+
+    // Fails twice, then succeeds after waiting 5ms + 10ms
+    device.initialize().await.expect("Failed to initialize device");
+    mock.push_read(Err(rusb::Error::Timeout));
+    mock.push_read(Err(rusb::Error::Io));
+    let start = std::time::Instant::now();
+    let entropy = device.read_entropy_retry(16, &policy(3)).await.expect("Failed to read entropy");
+    assert_eq!(entropy.len(), 16);
+    assert_eq!(mock.bulk_reads(), 3);
+    assert!(start.elapsed() >= Duration::from_millis(15));
+
+    // Gives up after max_attempts with the last error
+    for _ in 0..2 {
+        mock.push_read(Err(rusb::Error::Io));
+    }
+    let result = device.read_entropy_retry(16, &policy(2)).await;
+    assert!(matches!(result.unwrap_err(), QrngError::CommunicationError(_)));
+    assert_eq!(mock.bulk_reads(), 5);
+
+    // Not retried: a failed health test
+    device.set_health_monitor(HealthMonitor::for_min_entropy(8.0));
+    mock.push_read(Ok(vec![0x00; 16]));
+    let result = device.read_entropy_retry(16, &policy(5)).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+    assert_eq!(mock.bulk_reads(), 6);
+}
+
+#[tokio::test]
+async fn test_read_entropy_retry_reinitializes() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+    let policy = RetryPolicy { backoff: Duration::from_millis(1), ..RetryPolicy::default() };
+
+    // Two I/O errors each reopen the device before the next attempt
+    mock.push_read(Err(rusb::Error::Io));
+    mock.push_read(Err(rusb::Error::Io));
+    let entropy = device.read_entropy_retry(16, &policy).await.expect("Failed to read entropy");
+    assert_eq!(entropy.len(), 16);
+    assert_eq!(mock.opens(), 3);
+    assert!(mock.is_open());
+
+    // Disconnects are only retried when asked to reopen on them
+    mock.push_read(Err(rusb::Error::NoDevice));
+    let result = device.read_entropy_retry(16, &policy).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceDisconnected));
+    assert_eq!(mock.opens(), 3);
+
+    let policy = RetryPolicy { reinitialize_on: vec![RetryableError::Disconnected], ..policy };
+    mock.push_read(Err(rusb::Error::NoDevice));
+    device.read_entropy_retry(16, &policy).await.expect("Failed to read entropy");
+    assert_eq!(mock.opens(), 4);
+
+    // Reopening reapplies the configured FTDI settings, as initialize does
+    let mut config = device.config();
+    config.latency_timer = Some(1);
+    device.set_config(config);
+    mock.push_read(Err(rusb::Error::Io));
+    let issued = mock.commands().len();
+    let policy = RetryPolicy { reinitialize_on: vec![RetryableError::Communication], ..policy };
+    device.read_entropy_retry(16, &policy).await.expect("Failed to read entropy");
+    let latency = UsbCommand::Control {
+        request_type: FTDI_OUT_REQUEST_TYPE, request: SIO_SET_LATENCY_TIMER_REQUEST, value: 1, index: 1, data: Vec::new(),
+    };
+    assert!(mock.commands()[issued..].contains(&latency));
+}
+
+#[test]
+fn test_retry_delay_is_capped() {
+    let policy = RetryPolicy { max_attempts: usize::MAX, ..RetryPolicy::default() };
+    let mut delay = policy.backoff;
+    for _ in 0..200 {
+        delay = policy.next_delay(delay);
+    }
+    assert_eq!(delay, MAX_RETRY_DELAY);
+    assert_eq!(policy.next_delay(Duration::from_millis(10)), Duration::from_millis(20));
+
+    // A backoff over the cap is kept, and never overflows
+    let policy = RetryPolicy { backoff: Duration::MAX, ..RetryPolicy::default() };
+    assert_eq!(policy.next_delay(Duration::MAX), Duration::MAX);
+}
+
+#[tokio::test]
+async fn test_large_reads_are_chunked() {
+    let mock = MockTransport::new("MOCK-A");
+    mock.set_max_transfer(4096);
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    // Reads up to max_transfer stay a single transfer when the device keeps up
+    let whole = MockTransport::new("MOCK-B");
+    let mut single = whole.device();
+    single.initialize().await.expect("Failed to initialize device");
+    assert_eq!(single.read_entropy(DEFAULT_MAX_TRANSFER).await.unwrap().len(), DEFAULT_MAX_TRANSFER);
+    assert_eq!(whole.bulk_reads(), 1);
+
+    // Transfers are capped at max_transfer and topped up to the full size
+    let entropy = device.read_entropy(1024 * 1024).await.expect("Failed to read entropy");
+    assert_eq!(entropy.len(), 1024 * 1024);
+    assert_eq!(mock.largest_read(), DEFAULT_MAX_TRANSFER);
+    assert_eq!(mock.bulk_reads(), 256);
+
+    let mut config = device.config();
+    config.max_transfer = 4096;
+    device.set_config(config);
+    device.read_entropy(64 * 1024).await.expect("Failed to read entropy");
+    assert_eq!(mock.bulk_reads(), 256 + 16);
+
+    // The timeout is a deadline across transfers
+    device.set_read_timeout(Duration::from_millis(20));
+    mock.push_read(Ok(vec![0x5a; 4096]));
+    mock.push_read(Err(rusb::Error::Timeout));
+    let err = device.read_entropy(8192).await.unwrap_err();
+    assert!(matches!(err, QrngError::Timeout { requested: 8192, received: 4096, .. }), "{:?}", err);
+    assert!(mock.last_timeout().unwrap() < Duration::from_millis(20));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_reads_are_serialized() {
+    let mock = MockTransport::new("MOCK-A");
+    mock.set_max_transfer(100);
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    let readers: Vec<_> = (0..50).map(|_| {
+        let device = device.clone();
+        tokio::spawn(async move { device.read_entropy(1000).await })
+    }).collect();
+    let mut reads = Vec::new();
+    for reader in readers {
+        reads.push(reader.await.unwrap().expect("Failed to read entropy"));
+    }
+    assert_eq!(mock.bulk_reads(), 50 * 10);
+
+    // Each read is one unbroken stretch of the device's output
+    let mut reference = MockTransport::new("MOCK-A").device();
+    reference.initialize().await.expect("Failed to initialize device");
+    let stream = reference.read_entropy(50 * 1000).await.unwrap();
+    let mut offsets: Vec<usize> = reads.iter()
+        .map(|read| stream.chunks(1000).position(|chunk| chunk == read.as_slice()).expect("read was interleaved"))
+        .collect();
+    offsets.sort();
+    assert_eq!(offsets, (0..50).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_batch_and_into_reads_wait_for_read_permit() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    // As if a chunked read_entropy were between transfers
+    let permit = device.read_permits.clone().acquire_owned().await.unwrap();
+    let reads = mock.bulk_reads();
+    let into = tokio::spawn({
+        let device = device.clone();
+        async move { device.read_entropy_into(&mut [0u8; 16]).await }
+    });
+    let batch = tokio::spawn({
+        let device = device.clone();
+        async move { device.read_entropy_batch(&[16]).await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!into.is_finished() && !batch.is_finished());
+    assert_eq!(mock.bulk_reads(), reads);
+
+    drop(permit);
+    assert_eq!(into.await.unwrap().expect("Failed to read entropy"), 16);
+    assert_eq!(batch.await.unwrap().expect("Failed to read batch")[0].len(), 16);
+}
+
+#[tokio::test]
+async fn test_read_entropy_batch() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    assert!(matches!(device.read_entropy_batch(&[16]).await, Err(QrngError::DeviceNotInitialized)));
+    device.initialize().await.expect("Failed to initialize device");
+
+    let buffers = device.read_entropy_batch(&[16, 32, 64]).await.expect("Failed to read batch");
+    assert_eq!(buffers.iter().map(Vec::len).collect::<Vec<_>>(), [16, 32, 64]);
+    assert_eq!(mock.bulk_reads(), 3);
+
+    // Short transfers are topped up within each buffer
+    mock.push_read(Ok(vec![0x5a; 8]));
+    mock.push_read(Ok(vec![0xa5; 8]));
+    let buffers = device.read_entropy_batch(&[16]).await.expect("Failed to read batch");
+    assert_eq!(buffers[0][..8], [0x5a; 8]);
+    assert_eq!(buffers[0][8..], [0xa5; 8]);
+
+    let result = device.read_entropy_batch(&[16, 0]).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+}
+
+#[tokio::test]
+async fn test_read_entropy_into() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+
+    let mut buffer = [0u8; 32];
+    let result = device.read_entropy_into(&mut buffer).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceNotInitialized));
+    device.initialize().await.expect("Failed to initialize device");
+
+    // Full read
+    let n = device.read_entropy_into(&mut buffer).await.expect("Failed to read entropy");
+    assert_eq!(n, 32);
+    assert_ne!(buffer, [0u8; 32]);
+
+    // The allocating and buffer-filling paths agree on length, reusing one buffer
+    let mut reused = vec![0u8; 4096];
+    for size in [1, 64, 1000, 4096] {
+        let n = device.read_entropy_into(&mut reused[..size]).await.expect("Failed to read entropy");
+        let entropy = device.read_entropy(size).await.expect("Failed to read entropy");
+        assert_eq!(n, entropy.len());
+    }
+
+    // Short read reports the partial count and leaves the tail alone
+    let mut buffer = [0u8; 32];
+    mock.push_read(Ok(vec![0xa5; 10]));
+    let n = device.read_entropy_into(&mut buffer).await.expect("Failed to read entropy");
+    assert_eq!(n, 10);
+    assert_eq!(&buffer[..10], &[0xa5; 10]);
+    assert_eq!(&buffer[10..], &[0u8; 22]);
+
+    let result = device.read_entropy_into(&mut []).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+}
+
+#[tokio::test]
+async fn test_read_debiased() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    let entropy = device.read_debiased(100).await.expect("Failed to read debiased entropy");
+    assert_eq!(entropy.len(), 100);
+
+    // 0xAA debiases to all ones at half length
+    mock.push_read(Ok(vec![0xaa; 64]));
+    let entropy = device.read_debiased(16).await.expect("Failed to read debiased entropy");
+    assert_eq!(entropy, vec![0xff; 16]);
+
+    // A stuck source never yields enough output: 16 reads of 64 exhaust the budget
+    for _ in 0..16 {
+        mock.push_read(Ok(vec![0x00; 64]));
+    }
+    let result = device.read_debiased(16).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+}
+
+#[tokio::test]
+async fn test_read_entropy_debiased() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    // 0x96 debiases to 1001 per byte: the 32 raw bytes read give 16 output
+    // bytes, more than the 8 requested
+    mock.push_read(Ok(vec![0x96; 32]));
+    let entropy = device.read_entropy_debiased(8).await.expect("Failed to read debiased entropy");
+    assert_eq!(entropy, vec![0x99; 16]);
+
+    let entropy = device.read_entropy_debiased(100).await.expect("Failed to read debiased entropy");
+    assert!(entropy.len() >= 100);
+
+    for _ in 0..16 {
+        mock.push_read(Ok(vec![0xff; 64]));
+    }
+    let result = device.read_entropy_debiased(16).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+}
+
+#[tokio::test]
+async fn test_read_entropy_conditioned() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    // Reads twice the output length and conditions it down
+    mock.push_read(Ok(vec![0x5a; 96]));
+    let entropy = device.read_entropy_conditioned(48).await.expect("Failed to read conditioned entropy");
+    let mut conditioner = Sha256Conditioner::new();
+    conditioner.absorb(&[0x5a; 96]);
+    assert_eq!(entropy, conditioner.extract(48));
+    assert_eq!(mock.bulk_reads(), 1);
+}
+
+#[tokio::test]
+async fn test_read_entropy_short_reads() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    // Partial transfers are topped up to the requested size
+    mock.push_read(Ok(vec![0xa5; 10]));
+    mock.push_read(Ok(vec![0x5a; 12]));
+    mock.push_read(Ok(vec![0xc3; 10]));
+    let entropy = device.read_entropy(32).await.expect("Failed to read entropy");
+    assert_eq!(&entropy[..10], &[0xa5; 10]);
+    assert_eq!(&entropy[10..22], &[0x5a; 12]);
+    assert_eq!(&entropy[22..], &[0xc3; 10]);
+    assert_eq!(mock.bulk_reads(), 3);
+
+    // A device that keeps coming up short fails once the timeout passes
+    device.set_read_timeout(Duration::from_millis(20));
+    mock.push_read(Ok(vec![0xa5; 10]));
+    mock.set_responding(false);
+    let result = device.read_entropy(32).await;
+    assert!(matches!(result.unwrap_err(), QrngError::Timeout { requested: 32, .. }));
+
+    device.set_read_timeout(Duration::ZERO);
+    mock.set_responding(true);
+    mock.push_read(Ok(vec![0xa5; 10]));
+    let err = device.read_entropy(32).await.unwrap_err();
+    assert!(err.to_string().contains("got 10 of 32 bytes"), "unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_health_monitor_rejects_stuck_source() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.set_health_monitor(HealthMonitor::for_min_entropy(8.0));
+    device.initialize().await.expect("Failed to initialize device");
+
+    // Varied data passes
+    device.read_entropy(256).await.expect("Failed to read entropy");
+
+    // A stuck-at-zero source trips the repetition count test
+    mock.push_read(Ok(vec![0x00; 64]));
+    let result = device.read_entropy(64).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+}
+
+#[tokio::test]
+async fn test_read_entropy_checked() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    let entropy = device.read_entropy_checked(256).await.expect("Failed to read checked entropy");
+    assert_eq!(entropy.len(), 256);
+
+    // Without a configured monitor a stuck source is still caught
+    mock.push_read(Ok(vec![0xff; 64]));
+    let result = device.read_entropy_checked(64).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+}
+
+#[tokio::test]
+async fn test_entropy_stream() {
+    use futures::StreamExt;
+
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    // Nothing is read until the stream is polled
+    let stream = device.entropy_stream(64);
+    assert_eq!(mock.bulk_reads(), 0);
+
+    let chunks: Vec<_> = stream.take(10).collect().await;
+    assert_eq!(chunks.len(), 10);
+    for chunk in chunks {
+        assert_eq!(chunk.expect("Failed to read chunk").len(), 64);
+    }
+    assert_eq!(mock.bulk_reads(), 10);
+}
+
+#[tokio::test]
+async fn test_entropy_stream_ends_on_error() {
+    use futures::StreamExt;
+
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+    mock.push_read(Ok(vec![0x5a; 16]));
+    mock.push_read(Err(rusb::Error::Io));
+
+    let chunks: Vec<_> = device.entropy_stream(16).collect().await;
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].as_ref().unwrap(), &vec![0x5a; 16]);
+    assert!(matches!(chunks[1], Err(QrngError::CommunicationError(_))));
+}
+
+#[tokio::test]
+async fn test_device_stats() {
+    use crate::clock::ManualClock;
+
+    let mut manager = DeviceManager::new();
+    let clock = Arc::new(ManualClock::new());
+    manager.set_clock(clock.clone());
+    let mock = MockTransport::new("MOCK-A");
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    assert_eq!(manager.stats(&serial).await.unwrap(), DeviceStats::default());
+
+    for size in [16, 32, 64] {
+        manager.read_entropy(&serial, size).await.expect("Failed to read entropy");
+    }
+    mock.push_read(Err(rusb::Error::Io));
+    manager.read_entropy(&serial, 16).await.unwrap_err();
+
+    let stats = manager.stats(&serial).await.expect("Failed to get stats");
+    assert_eq!(stats.read_count, 3);
+    assert_eq!(stats.bytes_read, 112);
+    assert_eq!(stats.error_count, 1);
+    assert_eq!(stats.last_read, Some(clock.now()));
+    assert_eq!(manager.all_stats().await.get(&serial), Some(&stats));
+
+    // Removed devices take their stats with them
+    manager.remove_device(&serial).await.expect("Failed to remove device");
+    assert!(matches!(manager.stats(&serial).await.unwrap_err(), QrngError::DeviceNotFound(_)));
+    assert!(manager.all_stats().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_error_rate_alarm() {
+    use crate::clock::ManualClock;
+
+    let clock = Arc::new(ManualClock::new());
+    let mut manager = DeviceManager::new();
+    manager.set_clock(clock.clone());
+    manager.set_error_rate_alarm(ErrorRateAlarm {
+        window: Duration::from_secs(10),
+        threshold: 0.25,
+        min_reads: 4,
+    });
+
+    let mock = MockTransport::new("MOCK-A");
+    let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    // One failure in four reads: 25% trips the alarm once enough reads are in
+    for result in [Ok(vec![0x01; 8]), Err(rusb::Error::Io), Ok(vec![0x02; 8])] {
+        mock.push_read(result);
+        let _ = manager.read_entropy(&serial, 8).await;
+    }
+    assert!(!manager.is_degraded(&serial).await.unwrap());
+    manager.read_entropy(&serial, 8).await.expect("Failed to read entropy");
+    assert_eq!(manager.error_rate(&serial).await.unwrap(), 0.25);
+    assert!(manager.is_degraded(&serial).await.unwrap());
+
+    // Successes dilute the rate and clear the alarm
+    for _ in 0..4 {
+        manager.read_entropy(&serial, 8).await.expect("Failed to read entropy");
+    }
+    assert_eq!(manager.error_rate(&serial).await.unwrap(), 0.125);
+    assert!(!manager.is_degraded(&serial).await.unwrap());
+
+    // Old outcomes fall out of the window
+    clock.advance(Duration::from_secs(11));
+    assert_eq!(manager.error_rate(&serial).await.unwrap(), 0.0);
+
+    // Too few reads in the window never trip the alarm
+    mock.push_read(Err(rusb::Error::Io));
+    let _ = manager.read_entropy(&serial, 8).await;
+    assert_eq!(manager.error_rate(&serial).await.unwrap(), 1.0);
+    assert!(!manager.is_degraded(&serial).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_read_entropy_pooled() {
+    let manager = DeviceManager::new();
+    let mocks = [MockTransport::new("MOCK-A"), MockTransport::new("MOCK-B"), MockTransport::new("MOCK-C")];
+    for (mock, fill) in mocks.iter().zip([0xa1u8, 0xb2, 0xc3]) {
+        mock.push_read(Ok(vec![fill; 10]));
+        let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+        manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    }
+
+    // Each device contributes an equal share, in serial order
+    let entropy = manager.read_entropy_pooled(30).await.expect("Failed to read pooled entropy");
+    assert_eq!(&entropy[..10], &[0xa1; 10]);
+    assert_eq!(&entropy[10..20], &[0xb2; 10]);
+    assert_eq!(&entropy[20..], &[0xc3; 10]);
+}
+
+#[tokio::test]
+async fn test_fallback_without_devices() {
+    let mut manager = DeviceManager::new();
+    let result = manager.read_entropy_any(64).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceNotFound(_)));
+    assert!(!manager.has_fallback());
+
+    manager.set_fallback(Arc::new(FallbackSource));
+    assert!(manager.has_fallback());
+    let (serial, entropy) = manager.read_entropy_from_any(64).await.expect("Failed to read fallback entropy");
+    assert_eq!(serial, FALLBACK_SERIAL);
+    assert_eq!(entropy.len(), 64);
+
+    // Hardware takes precedence once present
+    let serial = manager.add_device(MockTransport::new("MOCK-A").device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    let (serial, _) = manager.read_entropy_from_any(64).await.expect("Failed to read entropy");
+    assert_eq!(serial, "MOCK-A");
+}
+
+#[tokio::test]
+async fn test_read_entropy_balanced() {
+    let manager = DeviceManager::new();
+    let mocks = [MockTransport::new("MOCK-A"), MockTransport::new("MOCK-B")];
+    for mock in &mocks {
+        let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+        manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    }
+
+    // A large read is split across both devices
+    let entropy = manager.read_entropy_balanced(64).await.expect("Failed to read balanced entropy");
+    assert_eq!(entropy.len(), 64);
+    assert_eq!(mocks.iter().map(MockTransport::bulk_reads).collect::<Vec<_>>(), vec![1, 1]);
+
+    // Single-byte reads only need one device, and alternate between them
+    for _ in 0..4 {
+        manager.read_entropy_balanced(1).await.expect("Failed to read balanced entropy");
+    }
+    assert_eq!(mocks.iter().map(MockTransport::bulk_reads).collect::<Vec<_>>(), vec![3, 3]);
+
+    // A failing device's share goes to the other one
+    mocks[0].push_read(Err(rusb::Error::Io));
+    let entropy = manager.read_entropy_balanced(64).await.expect("Failed to read balanced entropy");
+    assert_eq!(entropy.len(), 64);
+}
+
+#[tokio::test]
+async fn test_read_entropy_pooled_falls_back() {
+    let manager = DeviceManager::new();
+    let healthy = MockTransport::new("MOCK-A");
+    let failing = MockTransport::new("MOCK-B");
+    let uninitialized = MockTransport::new("MOCK-C");
+    healthy.push_read(Ok(vec![0xa1; 10]));
+    failing.push_read(Err(rusb::Error::Io));
+
+    for mock in [&healthy, &failing] {
+        let serial = manager.add_device(mock.device()).await.expect("Failed to add device");
+        manager.initialize_device(&serial).await.expect("Failed to initialize device");
+    }
+    manager.add_device(uninitialized.device()).await.expect("Failed to add device");
+
+    // MOCK-B's share is picked up by MOCK-A; MOCK-C is never asked
+    let entropy = manager.read_entropy_pooled(20).await.expect("Failed to read pooled entropy");
+    assert_eq!(entropy.len(), 20);
+    assert_eq!(&entropy[..10], &[0xa1; 10]);
+    assert_eq!(healthy.bulk_reads(), 2);
+    assert_eq!(failing.bulk_reads(), 1);
+    assert_eq!(uninitialized.bulk_reads(), 0);
+
+    // With nothing initialized there is nothing to pool
+    let empty = DeviceManager::new();
+    let result = empty.read_entropy_pooled(20).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceNotFound(_)));
+}
+
+#[tokio::test]
+async fn test_manager_entropy_stream() {
+    use futures::StreamExt;
+
+    let manager = DeviceManager::new();
+    let serial = manager.add_device(MockTransport::new("MOCK-A").device()).await.expect("Failed to add device");
+    manager.initialize_device(&serial).await.expect("Failed to initialize device");
+
+    let stream = manager.entropy_stream(&serial, 32).await.expect("Failed to open stream");
+    let chunks: Vec<_> = stream.take(3).collect().await;
+    for chunk in chunks {
+        assert_eq!(chunk.expect("Failed to read chunk").len(), 32);
+    }
+
+    // Unknown serials are rejected up front
+    let result = manager.entropy_stream("non-existent", 32).await;
+    assert!(matches!(result.err(), Some(QrngError::DeviceNotFound(_))));
+}
+
+#[tokio::test]
+async fn test_adaptive_stream_grows_for_fast_consumer() {
+    use futures::StreamExt;
+
+    let mut device = MockTransport::new("MOCK-A").device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    let chunking = AdaptiveChunking {
+        min_chunk: 16,
+        max_chunk: 1024,
+        initial_chunk: 16,
+        grow_below: Duration::from_millis(5),
+        shrink_above: Duration::from_millis(20),
+    };
+    let stream = device.adaptive_entropy_stream(chunking).expect("Failed to create stream");
+    let sizes: Vec<usize> = stream.take(10).map(|chunk| chunk.unwrap().len()).collect().await;
+
+    // Doubles on every prompt poll until it reaches the bound
+    assert_eq!(sizes, vec![16, 32, 64, 128, 256, 512, 1024, 1024, 1024, 1024]);
+}
+
+#[tokio::test]
+async fn test_adaptive_stream_shrinks_for_slow_consumer() {
+    use futures::StreamExt;
+
+    let mut device = MockTransport::new("MOCK-A").device();
+    device.initialize().await.expect("Failed to initialize device");
+
+    let chunking = AdaptiveChunking {
+        min_chunk: 16,
+        max_chunk: 1024,
+        initial_chunk: 128,
+        grow_below: Duration::from_millis(5),
+        shrink_above: Duration::from_millis(20),
+    };
+    let mut stream = Box::pin(device.adaptive_entropy_stream(chunking).expect("Failed to create stream"));
+    let mut sizes = Vec::new();
+    for _ in 0..5 {
+        sizes.push(stream.next().await.unwrap().unwrap().len());
+        tokio::time::sleep(Duration::from_millis(30)).await;
+    }
+
+    // Halves after every slow poll until it reaches the bound
+    assert_eq!(sizes, vec![128, 64, 32, 16, 16]);
+}
+
+#[test]
+fn test_adaptive_stream_rejects_bad_bounds() {
+    let device = MockTransport::new("MOCK-A").device();
+    let chunking = AdaptiveChunking { min_chunk: 512, max_chunk: 64, ..AdaptiveChunking::default() };
+    assert!(matches!(device.adaptive_entropy_stream(chunking).err(), Some(QrngError::InvalidState(_))));
+}
+
+#[test]
+fn test_rng_core() {
+    use rand_core::RngCore;
+
+    let mock = MockTransport::new("MOCK-A");
+    mock.push_read(Ok(vec![0x01, 0x02, 0x03, 0x04]));
+    mock.push_read(Ok(vec![0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80]));
+    let mut device = mock.device();
+
+    // Reads fail until the device is initialized
+    let mut buffer = [0u8; 16];
+    assert!(device.try_fill_bytes(&mut buffer).is_err());
+    block_on(device.initialize()).expect("Failed to initialize device");
+
+    // Integers are assembled little-endian
+    assert_eq!(device.next_u32(), 0x0403_0201);
+    assert_eq!(device.next_u64(), 0x8000_0000_0000_0001);
+
+    device.fill_bytes(&mut buffer);
+    assert_ne!(buffer, [0u8; 16]);
+}
+
 #[test]
 fn test_scan_devices() {
-    // Initialize logging
-    FmtSubscriber::builder()
-        .with_max_level(tracing::Level::INFO)
-        .with_target(false)
-        .with_thread_ids(false)
-        .with_thread_names(false)
-        .with_file(false)
-        .with_line_number(false)
-        .with_ansi(false)
-        .init();
+    crate::logging::init_logging(crate::logging::LogFormat::Compact);
 
     // Scan for devices
     let result = block_on(scan_devices());
@@ -144,4 +1677,107 @@ fn test_scan_devices() {
             println!("    Voltage: {:.1}V", status.voltage);
         }
     }
-} 
\ No newline at end of file
+} 
+#[test]
+fn test_ftdi_chip() {
+    for (bcd, chip, channels) in [
+        (0x0600, Some("FT232R"), 1),
+        (0x0700, Some("FT2232H"), 2),
+        (0x0800, Some("FT4232H"), 4),
+        (0x1000, Some("FT230X"), 1),
+        (0x0300, None, 1),
+    ] {
+        let mock = MockTransport::new("MOCK-A");
+        mock.set_device_version(bcd);
+        let device = mock.device();
+        assert_eq!(device.chip(), chip, "{:#06x}", bcd);
+        assert_eq!(device.channels(), channels, "{:#06x}", bcd);
+    }
+}
+
+#[tokio::test]
+async fn test_ftdi_settings() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    assert!(matches!(device.set_latency_timer(2).await, Err(QrngError::DeviceNotInitialized)));
+    device.initialize().await.expect("Failed to initialize device");
+    let request = |request, value| UsbCommand::Control {
+        request_type: FTDI_OUT_REQUEST_TYPE, request, value, index: 1, data: Vec::new(),
+    };
+
+    device.set_latency_timer(2).await.expect("Failed to set latency timer");
+    assert_eq!(mock.commands().last(), Some(&request(SIO_SET_LATENCY_TIMER_REQUEST, 2)));
+    let issued = mock.commands().len();
+    assert!(matches!(device.set_latency_timer(0).await, Err(QrngError::InvalidState(_))));
+    assert_eq!(mock.commands().len(), issued);
+
+    // Mode in the high byte, mask in the low byte
+    device.set_bitmode(0xff, 0x40).await.expect("Failed to set bitmode");
+    assert_eq!(mock.commands().last(), Some(&request(SIO_SET_BITMODE_REQUEST, 0x40ff)));
+
+    // Configured settings are applied by initialize
+    let mut config = device.config();
+    config.latency_timer = Some(1);
+    config.bitmode = Some((0x0f, 0x01));
+    device.set_config(config);
+    device.initialize().await.expect("Failed to initialize device");
+    let issued = mock.commands();
+    assert_eq!(&issued[issued.len() - 2..], &[
+        request(SIO_SET_LATENCY_TIMER_REQUEST, 1),
+        request(SIO_SET_BITMODE_REQUEST, 0x010f),
+    ]);
+
+    // A refused request fails initialization and leaves the device closed
+    mock.set_control_stalls(true);
+    let err = device.initialize().await.unwrap_err();
+    assert!(matches!(err, QrngError::CommunicationError(_)), "{:?}", err);
+    assert!(!device.is_initialized());
+    assert!(!mock.is_open());
+}
+
+#[tokio::test]
+async fn test_serial_is_cached() {
+    let device = MockTransport::new("MOCK-A").device();
+    assert_eq!(device.serial_cached(), Some("MOCK-A"));
+    // Held by an in-flight transfer
+    let _transport = device.transport.lock().await;
+    let serial = tokio::time::timeout(Duration::from_secs(1), device.serial()).await;
+    assert_eq!(serial.expect("serial waited on the transport lock").unwrap(), "MOCK-A");
+}
+
+#[tokio::test]
+async fn test_add_mock() {
+    let manager = DeviceManager::new();
+    assert_eq!(manager.add_mock("MOCK-A", 1).await.unwrap(), "MOCK-A");
+    manager.add_mock("MOCK-B", 2).await.unwrap();
+    let a = manager.read_entropy("MOCK-A", 64).await.expect("Failed to read MOCK-A");
+    let b = manager.read_entropy("MOCK-B", 64).await.expect("Failed to read MOCK-B");
+    assert_ne!(a, b);
+
+    // Same seed, same stream, whatever the serial or the way it is read
+    let replay = DeviceManager::new();
+    replay.add_mock("MOCK-A", 1).await.unwrap();
+    assert_eq!(replay.read_entropy("MOCK-A", 64).await.unwrap(), a);
+    let other = MockQrngDevice::new("OTHER", 2);
+    assert_eq!(other.serial(), "OTHER");
+    assert_eq!(other.read(64).await.unwrap(), b);
+}
+
+#[tokio::test]
+async fn test_benchmark() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    let result = device.benchmark(Duration::from_millis(10)).await;
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceNotInitialized));
+    device.initialize().await.expect("Failed to initialize device");
+
+    let report = device.benchmark(Duration::from_millis(20)).await.expect("Failed to benchmark");
+    assert!(report.elapsed >= Duration::from_millis(20));
+    assert_eq!(report.bytes, (mock.bulk_reads() * DEFAULT_MAX_TRANSFER) as u64);
+    assert_eq!(mock.largest_read(), DEFAULT_MAX_TRANSFER);
+    assert_eq!(report.bytes_per_sec, report.bytes as f64 / report.elapsed.as_secs_f64());
+
+    mock.set_responding(false);
+    device.set_read_timeout(Duration::from_millis(5));
+    assert!(matches!(device.benchmark(Duration::from_millis(20)).await, Err(QrngError::Timeout { .. })));
+}
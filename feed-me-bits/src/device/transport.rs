@@ -0,0 +1,165 @@
+use std::fmt::Debug;
+use std::time::Duration;
+use rusb::{Context, Device, DeviceDescriptor, DeviceHandle, Direction, TransferType};
+use tracing::warn;
+use crate::error::QrngError;
+use super::init::InitSequence;
+
+/// The USB operations a `QrngDevice` performs against its hardware.
+///
+/// USB failures come back as `QrngError::UsbError` carrying the raw
+/// `rusb::Error` so the device can map specific conditions (e.g. `NoDevice`).
+pub(crate) trait Transport: Send + Debug {
+    fn vendor_id(&self) -> u16;
+    fn product_id(&self) -> u16;
+    /// Major and minor USB release from the device descriptor.
+    fn usb_version(&self) -> (u8, u8);
+    /// Major and minor device release (`bcdDevice`) from the descriptor.
+    fn device_version(&self) -> (u8, u8);
+    fn bus_number(&self) -> u8;
+    fn address(&self) -> u8;
+    /// Open the device and run `sequence`, which claims `interface`.
+    fn initialize(&mut self, sequence: &dyn InitSequence, interface: u8) -> Result<(), QrngError>;
+    fn read_bulk(&mut self, endpoint: u8, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError>;
+    fn read_control(&mut self, request_type: u8, request: u8, value: u16, index: u16, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError>;
+    fn write_control(&mut self, request_type: u8, request: u8, value: u16, index: u16, buf: &[u8], timeout: Duration) -> Result<usize, QrngError>;
+    /// Addresses of `interface`'s bulk IN endpoints in the active
+    /// configuration, sorted.
+    fn bulk_in_endpoints(&self, interface: u8) -> Result<Vec<u8>, QrngError>;
+    fn claim_interface(&mut self, iface: u8) -> Result<(), QrngError>;
+    fn release_interface(&mut self, iface: u8) -> Result<(), QrngError>;
+    /// Release the interface claimed by `initialize` and drop the open
+    /// handle, if any.
+    fn close(&mut self);
+    fn manufacturer(&mut self) -> Result<String, QrngError>;
+    fn description(&mut self) -> Result<String, QrngError>;
+    fn serial(&mut self) -> Result<String, QrngError>;
+}
+
+/// Transport backed by a real libusb device. The handle is opened once by
+/// `initialize` and reused for every transfer.
+#[derive(Debug)]
+pub(crate) struct UsbTransport {
+    device: Device<Context>,
+    descriptor: DeviceDescriptor,
+    handle: Option<DeviceHandle<Context>>,
+    interface: u8,
+}
+
+impl UsbTransport {
+    pub(crate) fn new(device: Device<Context>, descriptor: DeviceDescriptor) -> Self {
+        Self { device, descriptor, handle: None, interface: 0 }
+    }
+
+    fn handle(&self) -> Result<&DeviceHandle<Context>, QrngError> {
+        self.handle.as_ref().ok_or(QrngError::DeviceNotInitialized)
+    }
+
+    // Descriptor strings are readable before initialize, e.g. to key the
+    // device by serial, so fall back to a short-lived handle.
+    fn read_string<F>(&self, read: F) -> Result<String, QrngError>
+    where
+        F: FnOnce(&DeviceHandle<Context>, &DeviceDescriptor) -> rusb::Result<String>,
+    {
+        match &self.handle {
+            Some(handle) => Ok(read(handle, &self.descriptor)?),
+            None => Ok(read(&self.device.open()?, &self.descriptor)?),
+        }
+    }
+}
+
+impl Transport for UsbTransport {
+    fn vendor_id(&self) -> u16 {
+        self.descriptor.vendor_id()
+    }
+
+    fn product_id(&self) -> u16 {
+        self.descriptor.product_id()
+    }
+
+    fn usb_version(&self) -> (u8, u8) {
+        let version = self.descriptor.usb_version();
+        (version.major(), version.minor())
+    }
+
+    fn device_version(&self) -> (u8, u8) {
+        let version = self.descriptor.device_version();
+        (version.major(), version.minor())
+    }
+
+    fn bus_number(&self) -> u8 {
+        self.device.bus_number()
+    }
+
+    fn address(&self) -> u8 {
+        self.device.address()
+    }
+
+    fn initialize(&mut self, sequence: &dyn InitSequence, interface: u8) -> Result<(), QrngError> {
+        let mut handle = self.device.open()?;
+        sequence.init(&mut handle)?;
+        self.handle = Some(handle);
+        self.interface = interface;
+        Ok(())
+    }
+
+    fn read_bulk(&mut self, endpoint: u8, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError> {
+        Ok(self.handle()?.read_bulk(endpoint, buf, timeout)?)
+    }
+
+    fn read_control(&mut self, request_type: u8, request: u8, value: u16, index: u16, buf: &mut [u8], timeout: Duration) -> Result<usize, QrngError> {
+        Ok(self.handle()?.read_control(request_type, request, value, index, buf, timeout)?)
+    }
+
+    fn write_control(&mut self, request_type: u8, request: u8, value: u16, index: u16, buf: &[u8], timeout: Duration) -> Result<usize, QrngError> {
+        Ok(self.handle()?.write_control(request_type, request, value, index, buf, timeout)?)
+    }
+
+    fn bulk_in_endpoints(&self, interface: u8) -> Result<Vec<u8>, QrngError> {
+        let config = self.device.active_config_descriptor()?;
+        let mut endpoints: Vec<u8> = config.interfaces()
+            .filter(|candidate| candidate.number() == interface)
+            .flat_map(|interface| interface.descriptors())
+            .flat_map(|setting| setting.endpoint_descriptors())
+            .filter(|endpoint| endpoint.direction() == Direction::In && endpoint.transfer_type() == TransferType::Bulk)
+            .map(|endpoint| endpoint.address())
+            .collect();
+        endpoints.sort_unstable();
+        endpoints.dedup();
+        Ok(endpoints)
+    }
+
+    fn claim_interface(&mut self, iface: u8) -> Result<(), QrngError> {
+        Ok(self.handle()?.claim_interface(iface)?)
+    }
+
+    fn release_interface(&mut self, iface: u8) -> Result<(), QrngError> {
+        Ok(self.handle()?.release_interface(iface)?)
+    }
+
+    fn close(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.release_interface(self.interface) {
+                warn!("Failed to release interface: {}", e);
+            }
+        }
+    }
+
+    fn manufacturer(&mut self) -> Result<String, QrngError> {
+        self.read_string(|handle, descriptor| handle.read_manufacturer_string_ascii(descriptor))
+    }
+
+    fn description(&mut self) -> Result<String, QrngError> {
+        self.read_string(|handle, descriptor| handle.read_product_string_ascii(descriptor))
+    }
+
+    fn serial(&mut self) -> Result<String, QrngError> {
+        self.read_string(|handle, descriptor| handle.read_serial_number_string_ascii(descriptor))
+    }
+}
+
+impl Drop for UsbTransport {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
@@ -1,5 +1,6 @@
 use thiserror::Error;
 use std::io;
+use std::time::Duration;
 
 #[derive(Debug, Error)]
 pub enum QrngError {
@@ -9,6 +10,17 @@ pub enum QrngError {
     DeviceNotFound(String),
     #[error("Device not initialized")]
     DeviceNotInitialized,
+    #[error("Device disconnected")]
+    DeviceDisconnected,
+    /// A USB transfer got no answer in time. Transient, unlike most other
+    /// USB failures, so worth retrying. `received` counts the bytes that
+    /// did arrive before the deadline.
+    #[error("Timed out after {elapsed:?} with {received} of {requested} bytes")]
+    Timeout { requested: usize, received: usize, elapsed: Duration },
+    /// The firmware has no status endpoint, so there are no readings to
+    /// report.
+    #[error("Device status not supported")]
+    StatusUnsupported,
     #[error("Communication error: {0}")]
     CommunicationError(String),
     #[error("Invalid state: {0}")]
@@ -0,0 +1,71 @@
+#[cfg(test)]
+use super::*;
+
+fn pseudo_random(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..len).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as u8
+    }).collect()
+}
+
+#[test]
+fn test_most_common_value() {
+    // Every byte value equally often
+    let uniform: Vec<u8> = (0..=255u8).cycle().take(256 * 64).collect();
+    let entropy = most_common_value(&uniform);
+    assert!(entropy > 7.0 && entropy <= 8.0, "uniform scored {}", entropy);
+
+    // A stuck source has no entropy
+    assert_eq!(most_common_value(&[0x42; 1024]), 0.0);
+
+    // Too few samples to estimate anything
+    assert_eq!(most_common_value(&[]), 0.0);
+    assert_eq!(most_common_value(&[0x42]), 0.0);
+}
+
+#[test]
+fn test_collision() {
+    let uniform = collision(&pseudo_random(16 * 1024));
+    // The confidence adjustment makes the bound conservative even for good data
+    assert!(uniform > 6.0 && uniform <= 8.0, "uniform scored {}", uniform);
+
+    // Mostly-zero bytes collide almost immediately
+    let near_constant: Vec<u8> = (0..16 * 1024).map(|i| if i % 64 == 0 { 0x01 } else { 0x00 }).collect();
+    let low = collision(&near_constant);
+    assert!(low < 1.0, "near-constant scored {}", low);
+    assert!(low < uniform);
+
+    assert_eq!(collision(&[0x00; 1024]), 0.0);
+    assert_eq!(collision(&[]), 0.0);
+}
+
+#[test]
+fn test_markov() {
+    let iid = markov(&pseudo_random(16 * 1024));
+    assert!(iid > 7.0 && iid <= 8.0, "IID-like scored {}", iid);
+
+    // Alternating bits with the odd glitch: balanced, but each bit predicts the next
+    let correlated: Vec<u8> = (0..16 * 1024).map(|i| if i % 50 == 0 { 0xa8 } else { 0xaa }).collect();
+    let low = markov(&correlated);
+    assert!(low < 1.0, "correlated scored {}", low);
+
+    assert_eq!(markov(&[0x00; 1024]), 0.0);
+    assert_eq!(markov(&[]), 0.0);
+}
+
+#[test]
+fn test_report_takes_minimum() {
+    let samples: Vec<u8> = (0..4096u32).map(|i| if i % 4 == 0 { i as u8 } else { 0 }).collect();
+    let report = min_entropy_report(&samples);
+
+    assert_eq!(report.sample_size, samples.len());
+    assert_eq!(report.confidence_level, CONFIDENCE_LEVEL);
+    assert!(!report.estimates.is_empty());
+    for estimate in &report.estimates {
+        assert!(report.min_entropy <= estimate.min_entropy);
+    }
+    assert!(report.estimates.iter().any(|estimate| estimate.min_entropy == report.min_entropy));
+}
@@ -0,0 +1,37 @@
+//! Randomness extractors for whitening raw device output.
+
+/// Classic von Neumann debiasing over the bit stream of `input`.
+///
+/// Bits are taken in pairs, most significant first: `00` and `11` are
+/// discarded, `01` yields 0 and `10` yields 1. Output bits are packed back
+/// into bytes and any incomplete final byte is dropped.
+///
+/// The output length depends on the data, not just on `input.len()`: an
+/// unbiased source yields about a quarter of its input, and a constant one
+/// yields nothing.
+pub fn von_neumann(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() / 4);
+    let mut current = 0u8;
+    let mut filled = 0;
+
+    for &byte in input {
+        for shift in (0..8).step_by(2).rev() {
+            let first = (byte >> (shift + 1)) & 1;
+            let second = (byte >> shift) & 1;
+            if first == second {
+                continue;
+            }
+            current = (current << 1) | first;
+            filled += 1;
+            if filled == 8 {
+                output.push(current);
+                current = 0;
+                filled = 0;
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests;
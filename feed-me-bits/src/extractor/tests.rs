@@ -0,0 +1,26 @@
+#[cfg(test)]
+use super::*;
+
+#[test]
+fn test_von_neumann_constant_bias() {
+    // 0xAA is 10 10 10 10: four 1 bits per input byte
+    assert_eq!(von_neumann(&[0xaa; 16]), vec![0xff; 8]);
+
+    // 0x55 is 01 01 01 01: four 0 bits per input byte
+    assert_eq!(von_neumann(&[0x55; 16]), vec![0x00; 8]);
+}
+
+#[test]
+fn test_von_neumann_discards_equal_pairs() {
+    assert!(von_neumann(&[0x00; 64]).is_empty());
+    assert!(von_neumann(&[0xff; 64]).is_empty());
+
+    // 0x96 is 10 01 01 10 -> 1001, 0x0f is 00 00 11 11 -> nothing
+    assert_eq!(von_neumann(&[0x96, 0x0f, 0x96]), vec![0b1001_1001]);
+}
+
+#[test]
+fn test_von_neumann_drops_partial_byte() {
+    assert!(von_neumann(&[0xaa]).is_empty());
+    assert_eq!(von_neumann(&[0xaa; 3]), vec![0xff]);
+}
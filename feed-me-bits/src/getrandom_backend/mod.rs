@@ -0,0 +1,74 @@
+//! Routing `getrandom` through a QRNG.
+//!
+//! Register `fill` with `getrandom::register_custom_getrandom!` (which
+//! needs getrandom's `custom` feature, and only takes over on targets
+//! without a native backend), then point it at a device with
+//! `set_global_source`:
+//!
+//! ```ignore
+//! getrandom::register_custom_getrandom!(feed_me_bits::getrandom_backend::fill);
+//! ```
+//!
+//! `fill` may be called from any number of threads at once. The source is
+//! behind a lock that is only held to clone the manager handle, and reads
+//! from the device are serialized by the device's own lock, so concurrent
+//! callers wait their turn rather than interleave transfers. Each call
+//! blocks its thread on the USB read, so don't call it from inside an async
+//! task.
+
+use std::num::NonZeroU32;
+use std::sync::RwLock;
+use crate::device::DeviceManager;
+use crate::error::QrngError;
+
+/// `fill` was called before `set_global_source`.
+pub const NOT_CONFIGURED: u32 = getrandom::Error::CUSTOM_START;
+/// The device is missing, uninitialized or unplugged.
+pub const DEVICE_UNAVAILABLE: u32 = getrandom::Error::CUSTOM_START + 1;
+/// The device didn't answer in time.
+pub const TIMEOUT: u32 = getrandom::Error::CUSTOM_START + 2;
+/// Any other failed read.
+pub const READ_FAILED: u32 = getrandom::Error::CUSTOM_START + 3;
+
+static SOURCE: RwLock<Option<(DeviceManager, String)>> = RwLock::new(None);
+
+/// Serve `fill` from `serial` on `manager`, replacing any earlier source.
+pub fn set_global_source(manager: DeviceManager, serial: impl Into<String>) {
+    *SOURCE.write().unwrap_or_else(|e| e.into_inner()) = Some((manager, serial.into()));
+}
+
+/// Stop serving `fill`; later calls fail with `NOT_CONFIGURED`.
+pub fn clear_global_source() {
+    *SOURCE.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Fill `dest` from the global source. Has the signature
+/// `register_custom_getrandom!` expects.
+pub fn fill(dest: &mut [u8]) -> Result<(), getrandom::Error> {
+    if dest.is_empty() {
+        return Ok(());
+    }
+    let Some((manager, serial)) = SOURCE.read().unwrap_or_else(|e| e.into_inner()).clone() else {
+        return Err(error(NOT_CONFIGURED));
+    };
+    let entropy = futures::executor::block_on(manager.read_entropy(&serial, dest.len()))
+        .map_err(|e| error(error_code(&e)))?;
+    dest.copy_from_slice(&entropy);
+    Ok(())
+}
+
+fn error_code(e: &QrngError) -> u32 {
+    match e {
+        QrngError::DeviceNotFound(_) | QrngError::DeviceNotInitialized | QrngError::DeviceDisconnected => DEVICE_UNAVAILABLE,
+        QrngError::Timeout { .. } => TIMEOUT,
+        _ => READ_FAILED,
+    }
+}
+
+fn error(code: u32) -> getrandom::Error {
+    // Every code is at least CUSTOM_START
+    NonZeroU32::new(code).expect("error codes are non-zero").into()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,31 @@
+#[cfg(test)]
+use super::*;
+use crate::device::mock::MockTransport;
+use tokio_test::block_on;
+
+#[test]
+fn test_fill_from_global_source() {
+    let mut buffer = [0u8; 32];
+    assert_eq!(fill(&mut buffer).unwrap_err().code().get(), NOT_CONFIGURED);
+
+    let manager = DeviceManager::new();
+    let mock = MockTransport::new("MOCK-A");
+    mock.push_read(Ok(vec![0x5a; 32]));
+    let serial = block_on(manager.add_device(mock.device())).expect("Failed to add device");
+    set_global_source(manager.clone(), &serial);
+    assert_eq!(fill(&mut buffer).unwrap_err().code().get(), DEVICE_UNAVAILABLE);
+
+    block_on(manager.initialize_device(&serial)).expect("Failed to initialize device");
+    fill(&mut buffer).expect("Failed to fill buffer");
+    assert_eq!(buffer, [0x5a; 32]);
+
+    // Callers on other threads share the source
+    let filled = std::thread::spawn(|| {
+        let mut buffer = [0u8; 16];
+        fill(&mut buffer).map(|()| buffer.len())
+    });
+    assert_eq!(filled.join().unwrap().expect("Failed to fill buffer"), 16);
+
+    clear_global_source();
+    assert_eq!(fill(&mut buffer).unwrap_err().code().get(), NOT_CONFIGURED);
+}
@@ -0,0 +1,275 @@
+//! Continuous health tests from NIST SP 800-90B section 4.4.
+//!
+//! Both tests watch the raw byte stream for signs that the noise source has
+//! failed, e.g. got stuck on one value or started favouring a few.
+
+use thiserror::Error;
+use crate::error::QrngError;
+use crate::estimate;
+
+/// False-positive probability used to derive cutoffs (2^-20, as suggested
+/// by SP 800-90B).
+pub const DEFAULT_ALPHA: f64 = 1.0 / (1u64 << 20) as f64;
+
+/// Adaptive proportion window for non-binary sources.
+pub const DEFAULT_WINDOW: usize = 512;
+
+/// Bytes read by `startup_check` when a device is initialized.
+pub const STARTUP_SAMPLE_SIZE: usize = 256;
+
+/// Fewest distinct byte values `startup_check` accepts in its sample.
+/// Samples shorter than `STARTUP_SAMPLE_SIZE` need one per 8 bytes.
+pub const STARTUP_MIN_DISTINCT: usize = 32;
+
+/// Min-entropy, in bits per byte, `startup_check` derives its repetition
+/// count cutoff from. Deliberately low, so only a stuck run fails it.
+pub const STARTUP_MIN_ENTROPY: f64 = 1.0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HealthTestError {
+    #[error("Repetition count test failed: {value:#04x} repeated {count} times")]
+    RepetitionCount { value: u8, count: usize },
+    #[error("Adaptive proportion test failed: {value:#04x} seen {count} times in {window} samples")]
+    AdaptiveProportion { value: u8, count: usize, window: usize },
+}
+
+/// Fails when the same value repeats `cutoff` times in a row.
+#[derive(Debug, Clone)]
+pub struct RepetitionCountTest {
+    cutoff: usize,
+    last: Option<u8>,
+    count: usize,
+}
+
+impl RepetitionCountTest {
+    pub fn new(cutoff: usize) -> Self {
+        Self { cutoff, last: None, count: 0 }
+    }
+
+    /// Cutoff for a source claiming `min_entropy` bits per byte.
+    pub fn for_min_entropy(min_entropy: f64) -> Self {
+        Self::new(1 + (-DEFAULT_ALPHA.log2() / min_entropy).ceil() as usize)
+    }
+
+    pub fn cutoff(&self) -> usize {
+        self.cutoff
+    }
+
+    pub fn push(&mut self, byte: u8) -> Result<(), HealthTestError> {
+        if self.last == Some(byte) {
+            self.count += 1;
+        } else {
+            self.last = Some(byte);
+            self.count = 1;
+        }
+
+        if self.count >= self.cutoff {
+            return Err(HealthTestError::RepetitionCount { value: byte, count: self.count });
+        }
+        Ok(())
+    }
+}
+
+/// Fails when the first value of a window reappears `cutoff` times within it.
+#[derive(Debug, Clone)]
+pub struct AdaptiveProportionTest {
+    window: usize,
+    cutoff: usize,
+    first: u8,
+    count: usize,
+    seen: usize,
+}
+
+impl AdaptiveProportionTest {
+    pub fn new(window: usize, cutoff: usize) -> Self {
+        Self { window, cutoff, first: 0, count: 0, seen: 0 }
+    }
+
+    /// Cutoff for a source claiming `min_entropy` bits per byte, using
+    /// `DEFAULT_WINDOW`.
+    pub fn for_min_entropy(min_entropy: f64) -> Self {
+        let p = 2f64.powf(-min_entropy);
+        Self::new(DEFAULT_WINDOW, binomial_cutoff(DEFAULT_WINDOW, p, DEFAULT_ALPHA))
+    }
+
+    pub fn cutoff(&self) -> usize {
+        self.cutoff
+    }
+
+    pub fn push(&mut self, byte: u8) -> Result<(), HealthTestError> {
+        if self.seen == 0 {
+            self.first = byte;
+            self.count = 1;
+        } else if byte == self.first {
+            self.count += 1;
+        }
+        self.seen = (self.seen + 1) % self.window;
+
+        if self.count >= self.cutoff {
+            let count = self.count;
+            self.seen = 0;
+            return Err(HealthTestError::AdaptiveProportion { value: byte, count, window: self.window });
+        }
+        Ok(())
+    }
+}
+
+/// Runs both continuous tests over every byte.
+#[derive(Debug, Clone)]
+pub struct HealthMonitor {
+    repetition_count: RepetitionCountTest,
+    adaptive_proportion: AdaptiveProportionTest,
+}
+
+impl HealthMonitor {
+    pub fn new(repetition_cutoff: usize, window: usize, proportion_cutoff: usize) -> Self {
+        Self {
+            repetition_count: RepetitionCountTest::new(repetition_cutoff),
+            adaptive_proportion: AdaptiveProportionTest::new(window, proportion_cutoff),
+        }
+    }
+
+    /// Cutoffs derived for a source claiming `min_entropy` bits per byte.
+    pub fn for_min_entropy(min_entropy: f64) -> Self {
+        Self {
+            repetition_count: RepetitionCountTest::for_min_entropy(min_entropy),
+            adaptive_proportion: AdaptiveProportionTest::for_min_entropy(min_entropy),
+        }
+    }
+
+    pub fn feed(&mut self, data: &[u8]) -> Result<(), HealthTestError> {
+        for &byte in data {
+            self.repetition_count.push(byte)?;
+            self.adaptive_proportion.push(byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// FIPS 140-2 continuous RNG test: each 32-bit block must differ from the
+/// one before it.
+#[derive(Debug, Clone, Default)]
+pub struct ContinuousRngTest {
+    last: Option<[u8; 4]>,
+}
+
+impl ContinuousRngTest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn check(&mut self, block: &[u8; 4]) -> Result<(), QrngError> {
+        if self.last.replace(*block) == Some(*block) {
+            return Err(QrngError::InvalidState(format!(
+                "Continuous RNG test failed: block {:02x?} repeated", block
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check every whole block of `data`; a trailing partial block is skipped.
+    pub fn check_all(&mut self, data: &[u8]) -> Result<(), QrngError> {
+        for block in data.chunks_exact(4) {
+            self.check(block.try_into().unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+/// Sanity check run on a fresh sample before a device is trusted. Catches
+/// sources that are plainly dead (stuck on one value), nearly so (too few
+/// distinct byte values) or that stall partway (a run failing the
+/// repetition count test), not subtle bias.
+pub fn startup_check(sample: &[u8]) -> Result<(), QrngError> {
+    let mut seen = [false; 256];
+    for &byte in sample {
+        seen[byte as usize] = true;
+    }
+    let min_distinct = STARTUP_MIN_DISTINCT.min(sample.len() / 8);
+    if seen.iter().filter(|&&seen| seen).count() < min_distinct {
+        return Err(QrngError::InvalidState("startup entropy check failed".to_string()));
+    }
+    let mut repetition = RepetitionCountTest::for_min_entropy(STARTUP_MIN_ENTROPY);
+    for &byte in sample {
+        repetition.push(byte)
+            .map_err(|e| QrngError::InvalidState(format!("startup entropy check failed: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// Quick quality summary of an entropy sample, in bits per byte.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct QualityReport {
+    pub sample_size: usize,
+    pub shannon_entropy: f64,
+    pub min_entropy: f64,
+}
+
+impl QualityReport {
+    pub fn measure(data: &[u8]) -> Self {
+        Self {
+            sample_size: data.len(),
+            shannon_entropy: shannon_entropy(data),
+            min_entropy: min_entropy(data),
+        }
+    }
+}
+
+/// Shannon entropy of the byte histogram of `data`, in bits per byte
+/// (0.0 to 8.0).
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+
+    let len = data.len() as f64;
+    counts.iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            p * (1.0 / p).log2()
+        })
+        .sum()
+}
+
+/// Min-entropy of `data` in bits per byte, from the most-common-value
+/// estimator. The estimate is a 99% lower bound, so it sits below 8.0 even
+/// for ideal data, further so for small samples.
+pub fn min_entropy(data: &[u8]) -> f64 {
+    estimate::most_common_value(data)
+}
+
+// Smallest C with P(1 + Binomial(window - 1, p) >= C) <= alpha
+fn binomial_cutoff(window: usize, p: f64, alpha: f64) -> usize {
+    let n = window - 1;
+    let ln_p = p.ln();
+    let ln_q = (1.0 - p).ln();
+
+    // ln P(X = k), built incrementally from ln C(n, k)
+    let mut ln_choose = 0.0;
+    let mut pmf = Vec::with_capacity(n + 1);
+    for k in 0..=n {
+        if k > 0 {
+            ln_choose += ((n - k + 1) as f64).ln() - (k as f64).ln();
+        }
+        pmf.push((ln_choose + k as f64 * ln_p + (n - k) as f64 * ln_q).exp());
+    }
+
+    let mut tail = 0.0;
+    for k in (0..=n).rev() {
+        tail += pmf[k];
+        if tail > alpha {
+            return k + 2;
+        }
+    }
+    1
+}
+
+#[cfg(test)]
+mod tests;
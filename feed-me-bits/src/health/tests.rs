@@ -0,0 +1,111 @@
+#[cfg(test)]
+use super::*;
+
+#[test]
+fn test_repetition_count_fires_on_stuck_source() {
+    let mut test = RepetitionCountTest::new(5);
+    for _ in 0..4 {
+        test.push(0x00).expect("Fired before reaching the cutoff");
+    }
+    assert_eq!(test.push(0x00), Err(HealthTestError::RepetitionCount { value: 0x00, count: 5 }));
+
+    // A changing value resets the run
+    let mut test = RepetitionCountTest::new(5);
+    for i in 0..1000u32 {
+        test.push((i % 3) as u8).expect("Fired on a changing stream");
+    }
+}
+
+#[test]
+fn test_adaptive_proportion_fires_on_biased_source() {
+    let mut test = AdaptiveProportionTest::new(16, 7);
+    let biased = [0x01, 0x01, 0x02, 0x01, 0x03, 0x01, 0x01, 0x04, 0x01, 0x01];
+    let result = biased.iter().try_for_each(|&byte| test.push(byte));
+    assert_eq!(result, Err(HealthTestError::AdaptiveProportion { value: 0x01, count: 7, window: 16 }));
+
+    // Counting restarts with every window
+    let mut test = AdaptiveProportionTest::new(4, 4);
+    for _ in 0..100 {
+        for byte in [0x01, 0x01, 0x01, 0x02] {
+            test.push(byte).expect("Fired across window boundaries");
+        }
+    }
+}
+
+#[test]
+fn test_monitor_fires_on_stuck_at_zero() {
+    let mut monitor = HealthMonitor::for_min_entropy(8.0);
+    let result = monitor.feed(&[0x00; 64]);
+    assert!(matches!(result, Err(HealthTestError::RepetitionCount { value: 0x00, .. })));
+}
+
+#[test]
+fn test_monitor_passes_varied_data() {
+    let mut monitor = HealthMonitor::for_min_entropy(8.0);
+    let data: Vec<u8> = (0..=255u8).cycle().take(64 * 1024).collect();
+    monitor.feed(&data).expect("Fired on varied data");
+}
+
+#[test]
+fn test_startup_check_rejects_stalled_run() {
+    let varied: Vec<u8> = (0..=255u8).collect();
+    startup_check(&varied).expect("Rejected varied data");
+
+    // Plenty of distinct values, then a stall
+    let mut stalled = varied.clone();
+    stalled.extend_from_slice(&[0xff; 64]);
+    let result = startup_check(&stalled);
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(msg) if msg.contains("Repetition count")));
+}
+
+#[test]
+fn test_derived_cutoffs() {
+    // SP 800-90B: C = 1 + ceil(20 / H)
+    assert_eq!(RepetitionCountTest::for_min_entropy(8.0).cutoff(), 4);
+    assert_eq!(RepetitionCountTest::for_min_entropy(1.0).cutoff(), 21);
+
+    // Lower claimed entropy tolerates more repeats of a value per window
+    let high = AdaptiveProportionTest::for_min_entropy(8.0).cutoff();
+    let low = AdaptiveProportionTest::for_min_entropy(1.0).cutoff();
+    assert!(high < low);
+    assert!(low < DEFAULT_WINDOW);
+}
+
+#[test]
+fn test_continuous_rng_fires_on_repeated_block() {
+    let mut test = ContinuousRngTest::new();
+    test.check(&[0x01, 0x02, 0x03, 0x04]).expect("Fired on the first block");
+    test.check(&[0x05, 0x06, 0x07, 0x08]).expect("Fired on a fresh block");
+    let result = test.check(&[0x05, 0x06, 0x07, 0x08]);
+    assert!(matches!(result, Err(QrngError::InvalidState(_))));
+
+    // Only adjacent blocks are compared
+    let mut test = ContinuousRngTest::new();
+    test.check_all(&[1, 1, 1, 1, 2, 2, 2, 2, 1, 1, 1, 1, 9]).expect("Fired on non-adjacent repeat");
+    assert!(test.check_all(&[2, 2, 2, 2, 2, 2, 2, 2]).is_err());
+}
+
+#[test]
+fn test_quality_scores() {
+    // xorshift output is close to uniform
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let uniform: Vec<u8> = (0..64 * 1024).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as u8
+    }).collect();
+    let report = QualityReport::measure(&uniform);
+    assert_eq!(report.sample_size, 64 * 1024);
+    assert!(report.shannon_entropy > 7.99, "uniform sample scored {}", report.shannon_entropy);
+    assert!(report.min_entropy > 7.0, "uniform sample scored {}", report.min_entropy);
+    assert!(report.min_entropy <= report.shannon_entropy);
+
+    let report = QualityReport::measure(&[0x00; 4096]);
+    assert_eq!(report.shannon_entropy, 0.0);
+    assert_eq!(report.min_entropy, 0.0);
+
+    // Two equally likely values carry exactly one bit
+    assert_eq!(shannon_entropy(&[0x00, 0xff].repeat(512)), 1.0);
+    assert_eq!(shannon_entropy(&[]), 0.0);
+}
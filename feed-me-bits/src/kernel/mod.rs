@@ -0,0 +1,96 @@
+//! Feeding device entropy into the Linux kernel's random pool.
+//!
+//! Crediting entropy with `RNDADDENTROPY` requires `CAP_SYS_ADMIN`; without
+//! it the ioctl fails with `EPERM`, surfaced as `QrngError::IoError`. On
+//! other platforms the feeders fail with `QrngError::InvalidState`.
+
+#[cfg(target_os = "linux")]
+use std::fs::{File, OpenOptions};
+#[cfg(target_os = "linux")]
+use std::os::fd::AsRawFd;
+use std::time::Duration;
+#[cfg(target_os = "linux")]
+use tracing::{debug, info};
+use crate::device::DeviceManager;
+use crate::error::QrngError;
+use crate::estimate;
+
+/// `_IOW('R', 0x03, int[2])` from `linux/random.h`.
+#[cfg(target_os = "linux")]
+const RNDADDENTROPY: libc::c_ulong = 0x4008_5203;
+
+/// Read `bytes_per_tick` bytes from `serial` every `interval` and add them
+/// to the kernel pool through `/dev/random`. Runs until a read or ioctl
+/// fails.
+///
+/// Each batch is credited with its estimated min-entropy (see
+/// `entropy_credit`) rather than a flat 8 bits per byte, so a degrading
+/// device credits less.
+pub async fn feed_kernel(manager: &DeviceManager, serial: &str, bytes_per_tick: usize, interval: Duration) -> Result<(), QrngError> {
+    feed(manager, Some(serial), bytes_per_tick, interval).await
+}
+
+/// `feed_kernel`, reading each batch from whichever device is available.
+pub async fn feed_kernel_entropy(manager: &DeviceManager, bytes_per_tick: usize, interval: Duration) -> Result<(), QrngError> {
+    feed(manager, None, bytes_per_tick, interval).await
+}
+
+#[cfg(target_os = "linux")]
+async fn feed(manager: &DeviceManager, serial: Option<&str>, bytes_per_tick: usize, interval: Duration) -> Result<(), QrngError> {
+    let random = OpenOptions::new().write(true).open("/dev/random")?;
+    info!("Feeding kernel entropy pool from {}", serial.unwrap_or("any device"));
+
+    let mut ticks = tokio::time::interval(interval);
+    loop {
+        ticks.tick().await;
+        let entropy = match serial {
+            Some(serial) => manager.read_entropy(serial, bytes_per_tick).await?,
+            None => manager.read_entropy_any(bytes_per_tick).await?,
+        };
+        let bits = entropy_credit(&entropy);
+        add_entropy(&random, &entropy, bits)?;
+        debug!("Added {} bytes crediting {} bits", entropy.len(), bits);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn feed(_manager: &DeviceManager, _serial: Option<&str>, _bytes_per_tick: usize, _interval: Duration) -> Result<(), QrngError> {
+    Err(QrngError::InvalidState("Feeding the kernel entropy pool is only supported on Linux".to_string()))
+}
+
+/// Entropy to credit for `data`, in bits: the SP 800-90B min-entropy
+/// estimate per byte times its length, rounded down.
+pub fn entropy_credit(data: &[u8]) -> u32 {
+    let per_byte = estimate::min_entropy_report(data).min_entropy.clamp(0.0, 8.0);
+    (per_byte * data.len() as f64).floor() as u32
+}
+
+/// Add `data` to the pool behind `random`, crediting `bits` of entropy.
+#[cfg(target_os = "linux")]
+pub fn add_entropy(random: &File, data: &[u8], bits: u32) -> Result<(), QrngError> {
+    let request = rand_pool_info(data, bits)?;
+    // SAFETY: `request` is a complete rand_pool_info whose buf_size matches
+    // the data that follows the header, and it outlives the call.
+    let result = unsafe { libc::ioctl(random.as_raw_fd(), RNDADDENTROPY as _, request.as_ptr()) };
+    if result < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+// struct rand_pool_info { int entropy_count; int buf_size; __u32 buf[0]; }
+pub(crate) fn rand_pool_info(data: &[u8], bits: u32) -> Result<Vec<u8>, QrngError> {
+    let too_large = || QrngError::InvalidState("Entropy batch too large".to_string());
+    let entropy_count = libc::c_int::try_from(bits).map_err(|_| too_large())?;
+    let buf_size = libc::c_int::try_from(data.len()).map_err(|_| too_large())?;
+
+    let mut request = Vec::with_capacity(8 + data.len().next_multiple_of(4));
+    request.extend_from_slice(&entropy_count.to_ne_bytes());
+    request.extend_from_slice(&buf_size.to_ne_bytes());
+    request.extend_from_slice(data);
+    request.resize(request.capacity(), 0);
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests;
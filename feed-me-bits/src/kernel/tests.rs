@@ -0,0 +1,31 @@
+#[cfg(test)]
+use super::*;
+
+#[test]
+fn test_entropy_credit_follows_min_entropy() {
+    assert_eq!(entropy_credit(&[0x00; 1024]), 0);
+
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let random: Vec<u8> = (0..4096).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as u8
+    }).collect();
+    let credit = entropy_credit(&random);
+    let expected = (estimate::min_entropy_report(&random).min_entropy * 4096.0).floor() as u32;
+    assert_eq!(credit, expected);
+    assert!(credit > 4096 * 5 && credit < 4096 * 8, "credited {} bits", credit);
+}
+
+#[test]
+fn test_rand_pool_info_layout() {
+    let request = rand_pool_info(&[1, 2, 3, 4, 5], 33).expect("Failed to build request");
+    assert_eq!(&request[..4], &33i32.to_ne_bytes());
+    assert_eq!(&request[4..8], &5i32.to_ne_bytes());
+    assert_eq!(&request[8..13], &[1, 2, 3, 4, 5]);
+
+    // Padded to whole u32 words
+    assert_eq!(request.len(), 16);
+    assert_eq!(&request[13..], &[0, 0, 0]);
+}
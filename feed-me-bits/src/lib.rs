@@ -1,8 +1,24 @@
 pub mod error;
+pub mod clock;
+pub mod conditioning;
 pub mod device;
+pub mod estimate;
+pub mod extractor;
+pub mod getrandom_backend;
+pub mod health;
+#[cfg(feature = "kernel")]
+pub mod kernel;
+pub mod logging;
+pub mod pool;
+pub mod rng;
+pub mod source;
 
 pub use error::QrngError;
-pub use device::{QrngDevice, DeviceStatus, scan_devices};
+pub use device::{
+    QrngDevice, DeviceStatus, scan_devices, scan_devices_filtered, scan_devices_matching, scan_devices_with_pids,
+};
+pub use rng::QrngRng;
+pub use source::{EntropySource, FallbackSource};
 
 // FTDI vendor ID
 const FTDI_VENDOR_ID: u16 = 0x0403;
@@ -0,0 +1,58 @@
+//! Process-wide `tracing` setup for binaries built on this crate.
+
+use std::fmt;
+use std::str::FromStr;
+use tracing_subscriber::EnvFilter;
+
+/// How `init_logging` formats events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Multi-line and human-oriented, for development.
+    Pretty,
+    /// One line per event.
+    #[default]
+    Compact,
+    /// One JSON object per line, for log aggregators.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" => Ok(Self::Pretty),
+            "compact" => Ok(Self::Compact),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown log format {:?}, expected pretty, compact or json", s)),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pretty => "pretty",
+            Self::Compact => "compact",
+            Self::Json => "json",
+        })
+    }
+}
+
+/// Install a global subscriber writing `format` to stderr, filtered by
+/// `RUST_LOG` (`info` when unset or invalid). Stdout is left alone, as it
+/// may carry entropy. Only the first call in a process, or the first since
+/// any other global subscriber was set, has an effect; later ones are
+/// ignored.
+pub fn init_logging(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr);
+    let _ = match format {
+        LogFormat::Pretty => builder.pretty().try_init(),
+        LogFormat::Compact => builder.compact().try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    };
+}
+
+#[cfg(test)]
+mod tests;
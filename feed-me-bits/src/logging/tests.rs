@@ -0,0 +1,18 @@
+#[cfg(test)]
+use super::*;
+
+#[test]
+fn test_init_logging_is_idempotent() {
+    init_logging(LogFormat::Json);
+    init_logging(LogFormat::Json);
+    init_logging(LogFormat::Pretty);
+    tracing::info!(format = %LogFormat::Json, "Logging initialized");
+}
+
+#[test]
+fn test_parse_log_format() {
+    assert_eq!("json".parse(), Ok(LogFormat::Json));
+    assert_eq!("Compact".parse(), Ok(LogFormat::Compact));
+    assert_eq!("pretty".parse::<LogFormat>().unwrap().to_string(), "pretty");
+    assert!("xml".parse::<LogFormat>().is_err());
+}
@@ -0,0 +1,257 @@
+//! Background pre-fetching of device entropy.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use crate::device::{DeviceManager, QrngDevice};
+use crate::error::QrngError;
+
+/// How long the refill task waits after a failed read before trying again.
+const ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Fill levels for an `EntropyPool`.
+///
+/// The refill task tops the pool up to `high_watermark` bytes, then sleeps
+/// until a `take` leaves fewer than `low_watermark` bytes behind.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolConfig {
+    pub high_watermark: usize,
+    pub low_watermark: usize,
+    /// Largest single device read made by the refill task.
+    pub chunk_size: usize,
+    /// Buffered bytes older than this are discarded rather than served.
+    pub max_age: Option<Duration>,
+}
+
+impl PoolConfig {
+    /// A pool holding up to `capacity` bytes, refilled once a quarter remains.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            high_watermark: capacity,
+            low_watermark: capacity / 4,
+            chunk_size: capacity.min(4096),
+            max_age: None,
+        }
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self::with_capacity(64 * 1024)
+    }
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    buffer: VecDeque<u8>,
+    /// When each read still (partly) in `buffer` arrived, and how many of
+    /// its bytes remain, oldest first.
+    arrivals: VecDeque<(Instant, usize)>,
+    error: Option<QrngError>,
+}
+
+impl PoolState {
+    fn push(&mut self, entropy: Vec<u8>) {
+        self.arrivals.push_back((Instant::now(), entropy.len()));
+        self.buffer.extend(entropy);
+    }
+
+    fn drain_into(&mut self, output: &mut Vec<u8>, n: usize) {
+        output.extend(self.buffer.drain(..n));
+        let mut remaining = n;
+        while remaining > 0 {
+            let Some((_, len)) = self.arrivals.front_mut() else {
+                break;
+            };
+            let used = remaining.min(*len);
+            *len -= used;
+            remaining -= used;
+            if *len == 0 {
+                self.arrivals.pop_front();
+            }
+        }
+    }
+
+    /// Drop bytes that have been buffered longer than `max_age`.
+    fn expire(&mut self, max_age: Option<Duration>) {
+        let Some(max_age) = max_age else {
+            return;
+        };
+        let mut stale = 0;
+        while let Some(&(arrived, len)) = self.arrivals.front() {
+            if arrived.elapsed() < max_age {
+                break;
+            }
+            stale += len;
+            self.arrivals.pop_front();
+        }
+        if stale > 0 {
+            debug!("Discarding {} stale bytes from the entropy pool", stale);
+            self.buffer.drain(..stale);
+        }
+    }
+
+    /// How long until the oldest buffered bytes go stale.
+    fn next_expiry(&self, max_age: Option<Duration>) -> Option<Duration> {
+        let (arrived, _) = self.arrivals.front()?;
+        Some(max_age?.saturating_sub(arrived.elapsed()))
+    }
+}
+
+/// Where the refill task reads from.
+enum Source {
+    Device(QrngDevice),
+    /// Goes through the manager, so its health checks and disconnect
+    /// handling apply to pool reads too.
+    Manager { manager: DeviceManager, serial: String },
+}
+
+impl Source {
+    async fn read(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        match self {
+            Source::Device(device) => device.read_entropy(size).await,
+            Source::Manager { manager, serial } => manager.read_entropy(serial, size).await,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<PoolState>,
+    /// Signalled by `take` when the pool drops below the low watermark.
+    refill: Notify,
+    /// Signalled by the refill task after every read attempt.
+    filled: Notify,
+}
+
+/// Entropy buffered ahead of demand by a background tokio task, so callers
+/// don't wait on USB latency unless they drain the pool.
+///
+/// Dropping the pool stops the task.
+#[derive(Debug)]
+pub struct EntropyPool {
+    shared: Arc<Shared>,
+    config: PoolConfig,
+    task: JoinHandle<()>,
+}
+
+impl EntropyPool {
+    /// Start filling a pool from `device`, which must already be initialized.
+    /// Must be called from within a tokio runtime.
+    pub fn new(device: QrngDevice, config: PoolConfig) -> Result<Self, QrngError> {
+        Self::spawn(Source::Device(device), config)
+    }
+
+    /// Start filling a pool from `serial` in `manager`, which must already be
+    /// initialized. Must be called from within a tokio runtime.
+    pub fn from_manager(manager: DeviceManager, serial: &str, config: PoolConfig) -> Result<Self, QrngError> {
+        Self::spawn(Source::Manager { manager, serial: serial.to_string() }, config)
+    }
+
+    fn spawn(source: Source, config: PoolConfig) -> Result<Self, QrngError> {
+        if config.chunk_size == 0 || config.low_watermark >= config.high_watermark {
+            return Err(QrngError::InvalidState("Invalid pool watermarks".to_string()));
+        }
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(PoolState::default()),
+            refill: Notify::new(),
+            filled: Notify::new(),
+        });
+        let task = tokio::spawn(refill(source, config, Arc::clone(&shared)));
+        Ok(Self { shared, config, task })
+    }
+
+    /// Bytes buffered and ready to take, not counting any gone stale.
+    pub fn available(&self) -> usize {
+        let mut state = self.shared.state.lock().unwrap();
+        state.expire(self.config.max_age);
+        state.buffer.len()
+    }
+
+    /// Take `size` bytes, waiting for the refill task if the pool runs dry.
+    /// A device error hit while this call waits is returned, and the bytes
+    /// gathered so far are discarded.
+    pub async fn take(&self, size: usize) -> Result<Vec<u8>, QrngError> {
+        if size == 0 {
+            return Err(QrngError::InvalidState("Invalid entropy size".to_string()));
+        }
+
+        let mut output = Vec::with_capacity(size);
+        loop {
+            let filled = self.shared.filled.notified();
+            tokio::pin!(filled);
+            filled.as_mut().enable();
+
+            {
+                let mut state = self.shared.state.lock().unwrap();
+                state.expire(self.config.max_age);
+                let n = state.buffer.len().min(size - output.len());
+                state.drain_into(&mut output, n);
+                if output.len() == size {
+                    if state.buffer.len() < self.config.low_watermark {
+                        self.shared.refill.notify_one();
+                    }
+                    return Ok(output);
+                }
+                if let Some(e) = state.error.take() {
+                    return Err(e);
+                }
+            }
+            self.shared.refill.notify_one();
+            filled.await;
+        }
+    }
+}
+
+impl Drop for EntropyPool {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn refill(source: Source, config: PoolConfig, shared: Arc<Shared>) {
+    loop {
+        let (wanted, next_expiry) = {
+            let mut state = shared.state.lock().unwrap();
+            state.expire(config.max_age);
+            (config.high_watermark - state.buffer.len(), state.next_expiry(config.max_age))
+        };
+        if wanted == 0 {
+            // A full pool still needs topping up once its oldest bytes expire
+            match next_expiry {
+                Some(expiry) => {
+                    let _ = tokio::time::timeout(expiry, shared.refill.notified()).await;
+                }
+                None => shared.refill.notified().await,
+            }
+            continue;
+        }
+
+        let result = source.read(wanted.min(config.chunk_size)).await;
+        let failed = result.is_err();
+        {
+            let mut state = shared.state.lock().unwrap();
+            match result {
+                Ok(entropy) => {
+                    state.push(entropy);
+                    state.error = None;
+                }
+                Err(e) => {
+                    warn!("Entropy pool refill failed: {}", e);
+                    state.error = Some(e);
+                }
+            }
+        }
+        shared.filled.notify_waiters();
+        if failed {
+            tokio::time::sleep(ERROR_BACKOFF).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
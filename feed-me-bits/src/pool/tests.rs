@@ -0,0 +1,113 @@
+#[cfg(test)]
+use super::*;
+use crate::device::DeviceManager;
+use crate::device::mock::MockTransport;
+
+async fn initialized_device(mock: &MockTransport) -> QrngDevice {
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+    device
+}
+
+async fn wait_for_available(pool: &EntropyPool, bytes: usize) {
+    for _ in 0..100 {
+        if pool.available() >= bytes {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("Pool only reached {} of {} bytes", pool.available(), bytes);
+}
+
+#[tokio::test]
+async fn test_pool_fills_to_high_watermark() {
+    let mock = MockTransport::new("MOCK-A");
+    let config = PoolConfig { high_watermark: 1024, low_watermark: 256, chunk_size: 128, max_age: None };
+    let pool = EntropyPool::new(initialized_device(&mock).await, config).expect("Failed to create pool");
+
+    wait_for_available(&pool, 1024).await;
+    assert_eq!(pool.available(), 1024);
+    assert_eq!(mock.bulk_reads(), 8);
+
+    // Taking above the low watermark leaves the refill task idle
+    pool.take(512).await.expect("Failed to take entropy");
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(pool.available(), 512);
+
+    // Dropping below it tops the pool back up
+    pool.take(300).await.expect("Failed to take entropy");
+    wait_for_available(&pool, 1024).await;
+}
+
+#[tokio::test]
+async fn test_pool_take_outpaces_refill() {
+    let mock = MockTransport::new("MOCK-A");
+    let config = PoolConfig { high_watermark: 256, low_watermark: 64, chunk_size: 64, max_age: None };
+    let pool = EntropyPool::new(initialized_device(&mock).await, config).expect("Failed to create pool");
+
+    // Each take is far larger than the pool can hold at once
+    for _ in 0..4 {
+        let entropy = pool.take(4096).await.expect("Failed to take entropy");
+        assert_eq!(entropy.len(), 4096);
+    }
+}
+
+#[tokio::test]
+async fn test_pool_surfaces_device_errors() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = initialized_device(&mock).await;
+    device.set_read_timeout(Duration::from_millis(5));
+    mock.set_responding(false);
+    let pool = EntropyPool::new(device, PoolConfig::default()).expect("Failed to create pool");
+
+    let result = pool.take(16).await;
+    assert!(matches!(result.unwrap_err(), QrngError::Timeout { .. }));
+
+    // Recovers once the device answers again
+    mock.set_responding(true);
+    let entropy = pool.take(16).await.expect("Failed to take entropy");
+    assert_eq!(entropy.len(), 16);
+}
+
+#[tokio::test]
+async fn test_pool_from_manager() {
+    let manager = DeviceManager::new();
+    let mock = MockTransport::new("MOCK-A");
+    manager.add_device(mock.device()).await.expect("Failed to add device");
+    manager.initialize_device("MOCK-A").await.expect("Failed to initialize device");
+    let pool = EntropyPool::from_manager(manager, "MOCK-A", PoolConfig::with_capacity(512))
+        .expect("Failed to create pool");
+
+    wait_for_available(&pool, 512).await;
+    let entropy = pool.take(100).await.expect("Failed to take entropy");
+    assert_eq!(entropy.len(), 100);
+
+    // An unknown serial surfaces through take
+    let pool = EntropyPool::from_manager(DeviceManager::new(), "MOCK-B", PoolConfig::with_capacity(512))
+        .expect("Failed to create pool");
+    assert!(matches!(pool.take(16).await.unwrap_err(), QrngError::DeviceNotFound(_)));
+}
+
+#[tokio::test]
+async fn test_pool_discards_stale_entropy() {
+    let mock = MockTransport::new("MOCK-A");
+    let config = PoolConfig { max_age: Some(Duration::from_millis(50)), ..PoolConfig::with_capacity(256) };
+    let pool = EntropyPool::new(initialized_device(&mock).await, config).expect("Failed to create pool");
+
+    wait_for_available(&pool, 256).await;
+    let reads = mock.bulk_reads();
+
+    // Once the buffered bytes age out the pool is refilled with fresh ones
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    assert!(mock.bulk_reads() > reads);
+    wait_for_available(&pool, 256).await;
+}
+
+#[tokio::test]
+async fn test_pool_rejects_bad_config() {
+    let device = MockTransport::new("MOCK-A").device();
+    let config = PoolConfig { high_watermark: 64, low_watermark: 64, chunk_size: 16, max_age: None };
+    assert!(matches!(EntropyPool::new(device.clone(), config).unwrap_err(), QrngError::InvalidState(_)));
+    let config = PoolConfig { chunk_size: 0, ..PoolConfig::default() };
+    assert!(matches!(EntropyPool::new(device, config).unwrap_err(), QrngError::InvalidState(_)));
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use rand_core::{CryptoRng, RngCore};
+use crate::device::QrngDevice;
+use crate::error::QrngError;
+use crate::source::EntropySource;
+
+/// Bytes fetched from the device per refill unless configured otherwise.
+pub const DEFAULT_BUFFER_SIZE: usize = 4096;
+
+/// Buffered `RngCore` over a `QrngDevice` or any other `EntropySource`.
+///
+/// Entropy is fetched in `buffer_size` blocks so small draws like
+/// `next_u32` don't each cost a USB transfer. The first block is fetched
+/// asynchronously on construction; later refills block the calling thread,
+/// so don't draw from inside an async task.
+#[derive(Debug)]
+pub struct QrngRng {
+    source: Arc<dyn EntropySource>,
+    buffer: Vec<u8>,
+    position: usize,
+    buffer_size: usize,
+}
+
+impl QrngRng {
+    pub async fn new(device: QrngDevice) -> Result<Self, QrngError> {
+        Self::with_buffer_size(device, DEFAULT_BUFFER_SIZE).await
+    }
+
+    pub async fn with_buffer_size(device: QrngDevice, buffer_size: usize) -> Result<Self, QrngError> {
+        Self::from_source(Arc::new(device), buffer_size).await
+    }
+
+    pub async fn from_source(source: Arc<dyn EntropySource>, buffer_size: usize) -> Result<Self, QrngError> {
+        if buffer_size == 0 {
+            return Err(QrngError::InvalidState("Invalid buffer size".to_string()));
+        }
+
+        let buffer = source.read(buffer_size).await?;
+        Ok(Self {
+            source,
+            buffer,
+            position: 0,
+            buffer_size,
+        })
+    }
+
+    /// Buffered bytes left before the next refill.
+    pub fn available(&self) -> usize {
+        self.buffer.len() - self.position
+    }
+
+    fn refill(&mut self) -> Result<(), QrngError> {
+        self.buffer = futures::executor::block_on(self.source.read(self.buffer_size))?;
+        self.position = 0;
+        Ok(())
+    }
+}
+
+impl RngCore for QrngRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill_bytes(dest) {
+            panic!("Failed to read entropy from QRNG device: {}", e);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        let mut filled = 0;
+        while filled < dest.len() {
+            if self.available() == 0 {
+                self.refill().map_err(rand_core::Error::new)?;
+            }
+            let n = self.available().min(dest.len() - filled);
+            dest[filled..filled + n].copy_from_slice(&self.buffer[self.position..self.position + n]);
+            self.position += n;
+            filled += n;
+        }
+        Ok(())
+    }
+}
+
+impl CryptoRng for QrngRng {}
+
+#[cfg(test)]
+mod tests;
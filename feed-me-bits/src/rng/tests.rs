@@ -0,0 +1,67 @@
+#[cfg(test)]
+use super::*;
+use crate::device::mock::MockTransport;
+use tokio_test::block_on;
+
+#[test]
+fn test_qrng_rng_refills() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    block_on(device.initialize()).expect("Failed to initialize device");
+
+    // The first block is fetched on construction
+    let mut rng = block_on(QrngRng::with_buffer_size(device, 64)).expect("Failed to create rng");
+    assert_eq!(mock.bulk_reads(), 1);
+    assert_eq!(rng.available(), 64);
+
+    // 1000 u64s is 8000 bytes, i.e. 125 blocks of 64
+    let values: Vec<u64> = (0..1000).map(|_| rng.next_u64()).collect();
+    assert_eq!(mock.bulk_reads(), 125);
+    assert_eq!(rng.available(), 0);
+
+    // Refilled blocks carry fresh data
+    let distinct: std::collections::HashSet<_> = values.iter().collect();
+    assert_eq!(distinct.len(), values.len());
+}
+
+#[test]
+fn test_qrng_rng_surfaces_errors() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    block_on(device.initialize()).expect("Failed to initialize device");
+
+    let mut rng = block_on(QrngRng::with_buffer_size(device, 8)).expect("Failed to create rng");
+    mock.push_read(Err(rusb::Error::Io));
+
+    let mut dest = [0u8; 16];
+    let err = rng.try_fill_bytes(&mut dest).unwrap_err();
+    let inner = err.inner().downcast_ref::<QrngError>().expect("Expected a QrngError");
+    assert!(matches!(inner, QrngError::CommunicationError(_)));
+}
+
+#[derive(Debug)]
+struct FixedSource(u8);
+
+impl EntropySource for FixedSource {
+    fn read(&self, size: usize) -> futures::future::BoxFuture<'_, Result<Vec<u8>, QrngError>> {
+        Box::pin(async move { Ok(vec![self.0; size]) })
+    }
+}
+
+#[test]
+fn test_qrng_rng_from_source() {
+    let mut rng = block_on(QrngRng::from_source(Arc::new(FixedSource(0x5a)), 8)).expect("Failed to create rng");
+    assert_eq!(rng.next_u64(), 0x5a5a_5a5a_5a5a_5a5a);
+
+    // Refills come from the same source
+    let mut dest = [0u8; 20];
+    rng.fill_bytes(&mut dest);
+    assert_eq!(dest, [0x5a; 20]);
+}
+
+#[test]
+fn test_qrng_rng_rejects_empty_buffer() {
+    let device = MockTransport::new("MOCK-A").device();
+    let result = block_on(QrngRng::with_buffer_size(device, 0));
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+}
@@ -0,0 +1,76 @@
+//! Producers of entropy that can stand in for one another.
+
+use std::fmt::Debug;
+use tracing::warn;
+use crate::device::{DeviceManager, QrngDevice};
+use crate::error::QrngError;
+use crate::pool::EntropyPool;
+
+/// Future returned by `EntropySource::read`, re-exported for implementors.
+pub use futures::future::BoxFuture;
+
+/// Something that hands out entropy on request.
+///
+/// Returns a boxed future rather than using `async fn` so sources can be
+/// held as `Arc<dyn EntropySource>`.
+pub trait EntropySource: Send + Sync + Debug {
+    /// Read exactly `size` bytes.
+    fn read(&self, size: usize) -> BoxFuture<'_, Result<Vec<u8>, QrngError>>;
+
+    /// Whether the bytes come from software rather than a hardware QRNG.
+    /// Deployments that require quantum entropy should refuse such sources.
+    fn is_fallback(&self) -> bool {
+        false
+    }
+}
+
+impl EntropySource for QrngDevice {
+    fn read(&self, size: usize) -> BoxFuture<'_, Result<Vec<u8>, QrngError>> {
+        Box::pin(self.read_entropy(size))
+    }
+}
+
+impl EntropySource for EntropyPool {
+    fn read(&self, size: usize) -> BoxFuture<'_, Result<Vec<u8>, QrngError>> {
+        Box::pin(self.take(size))
+    }
+}
+
+/// Reads from whichever device `read_entropy_any` picks, including the
+/// manager's fallback if it has one.
+impl EntropySource for DeviceManager {
+    fn read(&self, size: usize) -> BoxFuture<'_, Result<Vec<u8>, QrngError>> {
+        Box::pin(self.read_entropy_any(size))
+    }
+
+    fn is_fallback(&self) -> bool {
+        self.has_fallback()
+    }
+}
+
+/// The operating system's CSPRNG, for CI and development machines without a
+/// QRNG attached. Not quantum entropy, and flagged as a fallback.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FallbackSource;
+
+impl EntropySource for FallbackSource {
+    fn read(&self, size: usize) -> BoxFuture<'_, Result<Vec<u8>, QrngError>> {
+        Box::pin(async move {
+            if size == 0 {
+                return Err(QrngError::InvalidState("Invalid entropy size".to_string()));
+            }
+
+            warn!("Serving {} bytes from the software fallback, not a QRNG", size);
+            let mut buffer = vec![0u8; size];
+            getrandom::getrandom(&mut buffer).map_err(std::io::Error::from)?;
+            Ok(buffer)
+        })
+    }
+
+    fn is_fallback(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,35 @@
+#[cfg(test)]
+use super::*;
+use std::sync::Arc;
+use crate::device::mock::MockTransport;
+use crate::pool::PoolConfig;
+
+#[tokio::test]
+async fn test_sources_are_interchangeable() {
+    let mock = MockTransport::new("MOCK-A");
+    let mut device = mock.device();
+    device.initialize().await.expect("Failed to initialize device");
+    let manager = DeviceManager::new();
+    manager.add_device(device.clone()).await.expect("Failed to add device");
+    let pool = EntropyPool::new(device.clone(), PoolConfig::with_capacity(256)).expect("Failed to create pool");
+
+    let sources: Vec<Arc<dyn EntropySource>> = vec![Arc::new(device), Arc::new(pool), Arc::new(manager)];
+    for source in sources {
+        let entropy = source.read(32).await.expect("Failed to read entropy");
+        assert_eq!(entropy.len(), 32);
+        assert!(!source.is_fallback());
+    }
+}
+
+#[tokio::test]
+async fn test_fallback_source() {
+    let source = FallbackSource;
+    assert!(source.is_fallback());
+
+    let entropy = source.read(64).await.expect("Failed to read fallback entropy");
+    assert_eq!(entropy.len(), 64);
+    assert_ne!(entropy, vec![0u8; 64]);
+
+    let result = source.read(0).await;
+    assert!(matches!(result.unwrap_err(), QrngError::InvalidState(_)));
+}
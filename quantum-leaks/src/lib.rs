@@ -0,0 +1,8 @@
+pub mod loadtest;
+pub mod protocol;
+pub mod server;
+
+pub use server::{
+    grpc_routes, router, router_with_source, serve, serve_tls, serve_with_shutdown, ApiKeys, HealthCheckConfig, RateLimit,
+    ServerConfig, TlsConfig, API_KEYS_ENV,
+};
@@ -0,0 +1,272 @@
+//! Load generator for soak testing a running server's `/entropy` endpoint.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use feed_me_bits::QrngError;
+use http_body_util::{BodyExt, Empty};
+use hyper::body::Bytes;
+use hyper::{header, Request, StatusCode, Uri};
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use serde::Serialize;
+use tracing::warn;
+
+/// What `run_load_test` sends, and for how long.
+#[derive(Debug, Clone)]
+pub struct LoadTestConfig {
+    /// Plain HTTP base URL of the server, e.g. `http://127.0.0.1:8080`.
+    pub url: String,
+    /// Requests kept in flight at once.
+    pub concurrency: usize,
+    /// `/entropy` sizes in bytes, which each worker cycles through. Repeat
+    /// a size to weight it.
+    pub sizes: Vec<usize>,
+    /// Workers stop starting requests after this long.
+    pub duration: Duration,
+    /// A request still unanswered after this long is abandoned and counted
+    /// as a stall.
+    pub stall_timeout: Duration,
+    /// Sent as an `Authorization: Bearer` token.
+    pub api_key: Option<String>,
+}
+
+impl Default for LoadTestConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://127.0.0.1:8080".to_string(),
+            concurrency: 8,
+            sizes: vec![32, 1024, 64 * 1024],
+            duration: Duration::from_secs(60),
+            stall_timeout: Duration::from_secs(5),
+            api_key: None,
+        }
+    }
+}
+
+/// Result of `run_load_test`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadReport {
+    pub requests: u64,
+    /// 200 responses with as many bytes as requested.
+    pub successes: u64,
+    /// 429 responses.
+    pub rate_limited: u64,
+    /// Other statuses, short bodies and connection failures.
+    pub errors: u64,
+    /// Requests abandoned after `stall_timeout`.
+    pub stalls: u64,
+    /// Entropy bytes received by successful requests.
+    pub bytes: u64,
+    pub elapsed: Duration,
+    /// Over successful requests.
+    pub latency: Latency,
+    /// Longest stretch without a successful response, including the
+    /// stretches before the first and after the last.
+    pub longest_gap: Duration,
+    pub stall_timeout: Duration,
+}
+
+impl LoadReport {
+    pub fn requests_per_sec(&self) -> f64 {
+        self.requests as f64 / self.elapsed.as_secs_f64()
+    }
+
+    pub fn bytes_per_sec(&self) -> f64 {
+        self.bytes as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Share of requests that failed or stalled. Rate-limited requests
+    /// don't count, as the server answered them as configured.
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        (self.errors + self.stalls) as f64 / self.requests as f64
+    }
+
+    /// The server stopped responding: a request stalled, or no request
+    /// succeeded for at least `stall_timeout`.
+    pub fn stalled(&self) -> bool {
+        self.stalls > 0 || self.longest_gap >= self.stall_timeout
+    }
+}
+
+/// Latency percentiles by nearest rank, zero when nothing succeeded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Latency {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl Latency {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let rank = |percentile: usize| samples[(samples.len() * percentile).div_ceil(100).max(1) - 1];
+        Self { p50: rank(50), p90: rank(90), p99: rank(99), max: samples[samples.len() - 1] }
+    }
+}
+
+/// Counts kept by one worker, merged into the report at the end.
+#[derive(Default)]
+struct WorkerTally {
+    requests: u64,
+    successes: u64,
+    rate_limited: u64,
+    errors: u64,
+    stalls: u64,
+    bytes: u64,
+    latencies: Vec<Duration>,
+}
+
+/// When the last success arrived, and the longest gap between two.
+struct Gaps {
+    last_success: Instant,
+    longest: Duration,
+}
+
+impl Gaps {
+    fn record(&mut self, now: Instant) {
+        self.longest = self.longest.max(now - self.last_success);
+        self.last_success = now;
+    }
+}
+
+enum Outcome {
+    Success(usize),
+    RateLimited,
+    Failed(String),
+}
+
+/// Hammer `config.url` with `/entropy` requests from `concurrency` workers
+/// for `config.duration`, then report what came back. Fails only on a bad
+/// config; a server that refuses or ignores every request still gets a
+/// report.
+pub async fn run_load_test(config: &LoadTestConfig) -> Result<LoadReport, QrngError> {
+    if config.concurrency == 0 {
+        return Err(QrngError::InvalidState("concurrency must be at least 1".to_string()));
+    }
+    if config.sizes.is_empty() || config.sizes.contains(&0) {
+        return Err(QrngError::InvalidState("sizes must be non-empty and non-zero".to_string()));
+    }
+    let base = config.url.trim_end_matches('/');
+    // Checked once up front so workers can't fail on the URL alone
+    base.parse::<Uri>()
+        .map_err(|e| QrngError::InvalidState(format!("invalid URL {}: {}", config.url, e)))?;
+
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+    let started = Instant::now();
+    let deadline = started + config.duration;
+    let gaps = Arc::new(Mutex::new(Gaps { last_success: started, longest: Duration::ZERO }));
+
+    let workers: Vec<_> = (0..config.concurrency)
+        .map(|worker| {
+            let client = client.clone();
+            let config = config.clone();
+            let base = base.to_string();
+            let gaps = gaps.clone();
+            tokio::spawn(async move {
+                let mut tally = WorkerTally::default();
+                // Workers start at different sizes so the mix holds at any
+                // moment, not just on average
+                for n in worker.. {
+                    if Instant::now() >= deadline {
+                        break;
+                    }
+                    let size = config.sizes[n % config.sizes.len()];
+                    let sent = Instant::now();
+                    let request = fetch(&client, &base, config.api_key.as_deref(), size);
+                    let outcome = tokio::time::timeout(config.stall_timeout, request).await;
+                    tally.requests += 1;
+                    match outcome {
+                        Ok(Outcome::Success(bytes)) => {
+                            let now = Instant::now();
+                            tally.successes += 1;
+                            tally.bytes += bytes as u64;
+                            tally.latencies.push(now - sent);
+                            gaps.lock().unwrap().record(now);
+                        }
+                        Ok(Outcome::RateLimited) => tally.rate_limited += 1,
+                        Ok(Outcome::Failed(reason)) => {
+                            tally.errors += 1;
+                            if tally.errors == 1 {
+                                warn!("Load test request failed: {}", reason);
+                            }
+                        }
+                        Err(_) => {
+                            tally.stalls += 1;
+                            warn!("No response to a {} byte request within {:?}", size, config.stall_timeout);
+                        }
+                    }
+                }
+                tally
+            })
+        })
+        .collect();
+
+    let mut report = LoadReport {
+        requests: 0,
+        successes: 0,
+        rate_limited: 0,
+        errors: 0,
+        stalls: 0,
+        bytes: 0,
+        elapsed: Duration::ZERO,
+        latency: Latency::default(),
+        longest_gap: Duration::ZERO,
+        stall_timeout: config.stall_timeout,
+    };
+    let mut latencies = Vec::new();
+    for worker in workers {
+        let tally = worker.await
+            .map_err(|e| QrngError::CommunicationError(format!("load test worker failed: {}", e)))?;
+        report.requests += tally.requests;
+        report.successes += tally.successes;
+        report.rate_limited += tally.rate_limited;
+        report.errors += tally.errors;
+        report.stalls += tally.stalls;
+        report.bytes += tally.bytes;
+        latencies.extend(tally.latencies);
+    }
+    let finished = Instant::now();
+    let mut gaps = gaps.lock().unwrap();
+    gaps.record(finished);
+    report.elapsed = finished - started;
+    report.latency = Latency::from_samples(latencies);
+    report.longest_gap = gaps.longest;
+    Ok(report)
+}
+
+async fn fetch(client: &Client<HttpConnector, Empty<Bytes>>, base: &str, api_key: Option<&str>, size: usize) -> Outcome {
+    let mut request = Request::get(format!("{}/entropy?bytes={}", base, size));
+    if let Some(key) = api_key {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", key));
+    }
+    let request = match request.body(Empty::new()) {
+        Ok(request) => request,
+        Err(e) => return Outcome::Failed(e.to_string()),
+    };
+    let response = match client.request(request).await {
+        Ok(response) => response,
+        Err(e) => return Outcome::Failed(e.to_string()),
+    };
+    let status = response.status();
+    let body = match response.into_body().collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) => return Outcome::Failed(e.to_string()),
+    };
+    match status {
+        StatusCode::OK if body.len() == size => Outcome::Success(size),
+        StatusCode::OK => Outcome::Failed(format!("{} bytes returned for a {} byte request", body.len(), size)),
+        StatusCode::TOO_MANY_REQUESTS => Outcome::RateLimited,
+        status => Outcome::Failed(format!("{}: {}", status, String::from_utf8_lossy(&body))),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,85 @@
+#[cfg(test)]
+use super::*;
+use std::net::SocketAddr;
+use feed_me_bits::device::DeviceManager;
+use crate::server::{router, ServerConfig};
+
+async fn mock_server() -> SocketAddr {
+    let manager = DeviceManager::new();
+    manager.add_mock("MOCK-LOAD", 7).await.unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = router(manager, ServerConfig::default());
+    tokio::spawn(async move { axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await });
+    addr
+}
+
+#[tokio::test]
+async fn test_load_against_mock_server() {
+    let addr = mock_server().await;
+    let config = LoadTestConfig {
+        url: format!("http://{}", addr),
+        concurrency: 4,
+        sizes: vec![32, 1024, 4096],
+        duration: Duration::from_millis(300),
+        stall_timeout: Duration::from_secs(2),
+        ..LoadTestConfig::default()
+    };
+    let report = run_load_test(&config).await.expect("Failed to run load test");
+
+    assert!(report.successes > 0);
+    assert!(report.error_rate() < 0.01, "error rate {}", report.error_rate());
+    assert!(!report.stalled());
+    assert!(report.bytes >= report.successes * 32);
+    assert!(report.latency.p50 <= report.latency.p99 && report.latency.p99 <= report.latency.max);
+    assert!(report.bytes_per_sec() > 0.0);
+}
+
+#[tokio::test]
+async fn test_load_detects_unresponsive_server() {
+    // Accepts connections and never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            held.push(socket);
+        }
+    });
+    let config = LoadTestConfig {
+        url: format!("http://{}", addr),
+        concurrency: 2,
+        duration: Duration::from_millis(200),
+        stall_timeout: Duration::from_millis(100),
+        ..LoadTestConfig::default()
+    };
+    let report = run_load_test(&config).await.expect("Failed to run load test");
+
+    assert_eq!(report.successes, 0);
+    assert!(report.stalls > 0);
+    assert!(report.stalled());
+    assert_eq!(report.latency, Latency::default());
+}
+
+#[tokio::test]
+async fn test_load_rejects_bad_config() {
+    let bad = [
+        LoadTestConfig { concurrency: 0, ..LoadTestConfig::default() },
+        LoadTestConfig { sizes: Vec::new(), ..LoadTestConfig::default() },
+        LoadTestConfig { sizes: vec![0], ..LoadTestConfig::default() },
+        LoadTestConfig { url: "not a url".to_string(), ..LoadTestConfig::default() },
+    ];
+    for config in bad {
+        assert!(matches!(run_load_test(&config).await, Err(QrngError::InvalidState(_))), "{:?}", config);
+    }
+}
+
+#[test]
+fn test_latency_percentiles() {
+    let samples = (1..=100).map(Duration::from_millis).collect();
+    let latency = Latency::from_samples(samples);
+    assert_eq!(latency.p50, Duration::from_millis(50));
+    assert_eq!(latency.p90, Duration::from_millis(90));
+    assert_eq!(latency.p99, Duration::from_millis(99));
+    assert_eq!(latency.max, Duration::from_millis(100));
+}
@@ -1,24 +1,581 @@
-use feed_me_bits::{scan_devices, QrngDevice};
+use clap::{Parser, Subcommand};
+use feed_me_bits::device::{DeviceInfo, DeviceManager};
+use feed_me_bits::logging::{init_logging, LogFormat};
+use feed_me_bits::DeviceStatus;
+use feed_me_bits::scan_devices;
+use quantum_leaks::loadtest::{run_load_test, LoadTestConfig};
+use quantum_leaks::{serve_with_shutdown, ApiKeys, RateLimit, ServerConfig, API_KEYS_ENV};
+use serde::Serialize;
 use std::error::Error;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
-fn main() -> Result<(), Box<dyn Error>> {
+/// Quantum Leaks - QRNG entropy server and diagnostics.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Log format: pretty, compact or json. Levels come from RUST_LOG.
+    #[arg(long, global = true, env = "QUANTUM_LEAKS_LOG_FORMAT", default_value_t = LogFormat::Compact)]
+    log_format: LogFormat,
+    /// Runs the server when omitted.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Initialize every device and serve entropy over HTTP.
+    Serve {
+        /// Address to listen on instead of the default.
+        #[arg(long)]
+        addr: Option<SocketAddr>,
+        /// Limit each client IP to this many entropy bytes per second.
+        #[arg(long, value_parser = parse_rate)]
+        rate_limit: Option<f64>,
+        /// Bytes a rate-limited client can request at once; defaults to
+        /// one second's worth.
+        #[arg(long, requires = "rate_limit")]
+        burst: Option<usize>,
+        /// Limit all clients together to this many entropy bytes per
+        /// second, with one second's worth of burst.
+        #[arg(long, value_parser = parse_rate)]
+        global_rate_limit: Option<f64>,
+        /// Require one of the API keys in this file, one per line. Keys
+        /// are otherwise read from the comma-separated
+        /// QUANTUM_LEAKS_API_KEYS; with neither the API is open.
+        #[arg(long)]
+        api_keys_file: Option<PathBuf>,
+        /// Also serve the gRPC API on this address.
+        #[arg(long)]
+        grpc_addr: Option<SocketAddr>,
+        /// Also serve the EGD protocol on a Unix socket at this path.
+        #[arg(long)]
+        egd_socket: Option<PathBuf>,
+        /// Serve this many simulated devices instead of scanning USB.
+        #[cfg(feature = "mock")]
+        #[arg(long, default_value_t = 0)]
+        mock_devices: usize,
+    },
+    /// List connected devices.
+    #[command(alias = "list")]
+    Scan {
+        /// Print the device details as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Initialize each device and include its status.
+        #[arg(long)]
+        include_status: bool,
+    },
+    /// List connected devices with their health and error counters.
+    Inventory {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Read raw entropy to a file or stdout.
+    Read {
+        /// Device to read from; any available device when omitted.
+        #[arg(long)]
+        serial: Option<String>,
+        #[arg(long)]
+        bytes: usize,
+        /// Write here instead of stdout.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Write raw entropy to stdout until interrupted or the reader goes away.
+    Stream {
+        /// Device to read from; the first one found when omitted.
+        #[arg(long)]
+        serial: Option<String>,
+        /// Stop after this many bytes.
+        #[arg(long)]
+        total_bytes: Option<u64>,
+        /// Bytes per device read.
+        #[arg(long, default_value_t = 4096)]
+        chunk_size: usize,
+    },
+    /// Print a device's status once.
+    Status {
+        #[arg(long)]
+        serial: String,
+    },
+    /// Measure a device's read throughput.
+    Bench {
+        /// Device to benchmark; the first one found when omitted.
+        #[arg(long)]
+        serial: Option<String>,
+        /// How long to read for.
+        #[arg(long, default_value_t = 5)]
+        seconds: u64,
+    },
+    /// Poll a device's status and print it until interrupted.
+    Watch {
+        #[arg(long)]
+        serial: String,
+        /// Milliseconds between polls.
+        #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
+    /// Load a running server's `/entropy` endpoint and report throughput,
+    /// latency, errors and stalls.
+    Loadtest {
+        /// Plain HTTP base URL of the server.
+        #[arg(long, default_value = "http://127.0.0.1:8080")]
+        url: String,
+        /// Requests kept in flight at once.
+        #[arg(long, default_value_t = 8, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        concurrency: usize,
+        /// Comma-separated request sizes in bytes, cycled through by each
+        /// worker. Repeat a size to weight it.
+        #[arg(long, value_delimiter = ',', default_value = "32,1024,65536")]
+        sizes: Vec<usize>,
+        /// How long to run for.
+        #[arg(long, default_value_t = 60)]
+        seconds: u64,
+        /// Milliseconds without a response before a request counts as
+        /// stalled.
+        #[arg(long, default_value_t = 5000)]
+        stall_timeout: u64,
+        /// Sent as an `Authorization: Bearer` token.
+        #[arg(long, env = "QUANTUM_LEAKS_API_KEY")]
+        api_key: Option<String>,
+        /// Print the report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// A rate limit in bytes per second, which must be positive and finite.
+fn parse_rate(arg: &str) -> Result<f64, String> {
+    let rate: f64 = arg.parse().map_err(|e| format!("{}", e))?;
+    if !(rate.is_finite() && rate > 0.0) {
+        return Err("must be a positive number of bytes per second".to_string());
+    }
+    Ok(rate)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    init_logging(cli.log_format);
+
+    let command = cli.command.unwrap_or(Command::Serve {
+        addr: None,
+        rate_limit: None,
+        burst: None,
+        global_rate_limit: None,
+        api_keys_file: None,
+        grpc_addr: None,
+        egd_socket: None,
+        #[cfg(feature = "mock")]
+        mock_devices: 0,
+    });
+    let result = match command {
+        Command::Serve {
+            addr,
+            rate_limit,
+            burst,
+            global_rate_limit,
+            api_keys_file,
+            grpc_addr,
+            egd_socket,
+            #[cfg(feature = "mock")]
+            mock_devices,
+        } => {
+            let defaults = ServerConfig::default();
+            let rate_limit = rate_limit.map(|bytes_per_second| RateLimit {
+                bytes_per_second,
+                burst: burst.unwrap_or(bytes_per_second.ceil() as usize),
+            });
+            let global_rate_limit = global_rate_limit.map(|bytes_per_second| RateLimit {
+                bytes_per_second,
+                burst: bytes_per_second.ceil() as usize,
+            });
+            match api_keys_file.map_or_else(|| Ok(ApiKeys::from_env(API_KEYS_ENV)), |path| ApiKeys::from_file(&path)) {
+                Ok(api_keys) => {
+                    let config = ServerConfig {
+                        bind_addr: addr.unwrap_or(defaults.bind_addr),
+                        rate_limit,
+                        global_rate_limit,
+                        api_keys,
+                        grpc_addr,
+                        egd_socket,
+                        ..defaults
+                    };
+                    #[cfg(feature = "mock")]
+                    if mock_devices > 0 {
+                        return exit_code(run_mock_server(config, mock_devices).await);
+                    }
+                    run_server(config).await
+                }
+                Err(e) => Err(e.into()),
+            }
+        }
+        Command::Scan { json, include_status } => scan(json, include_status).await,
+        Command::Inventory { json } => inventory(json).await,
+        Command::Read { serial, bytes, out } => read(serial, bytes, out).await,
+        Command::Stream { serial, total_bytes, chunk_size } => stream(serial, total_bytes, chunk_size).await,
+        Command::Status { serial } => status(&serial).await,
+        Command::Bench { serial, seconds } => bench(serial, Duration::from_secs(seconds)).await,
+        Command::Watch { serial, interval } => watch(&serial, Duration::from_millis(interval)).await,
+        Command::Loadtest { url, concurrency, sizes, seconds, stall_timeout, api_key, json } => {
+            let config = LoadTestConfig {
+                url,
+                concurrency,
+                sizes,
+                duration: Duration::from_secs(seconds),
+                stall_timeout: Duration::from_millis(stall_timeout),
+                api_key,
+            };
+            loadtest(&config, json).await
+        }
+    };
+    exit_code(result)
+}
+
+fn exit_code(result: Result<(), Box<dyn Error>>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run_server(config: ServerConfig) -> Result<(), Box<dyn Error>> {
     println!("Quantum Leaks - QRNG Entropy Server");
     println!("Scanning for devices...");
 
-    let devices = scan_devices()?;
+    let devices = scan_devices().await?;
     println!("\nFound {} QRNG device(s)", devices.len());
 
+    let manager = DeviceManager::new();
     for device in devices {
         println!("\nDevice Information:");
         println!("Vendor ID: 0x{:04x}", device.vendor_id());
         println!("Product ID: 0x{:04x}", device.product_id());
-        println!("Manufacturer: {}", device.manufacturer()?);
-        println!("Description: {}", device.description()?);
-        println!("Serial: {}", device.serial()?);
+        let (major, minor) = device.usb_version();
+        println!("USB Version: {}.{}", major, minor);
+        println!("Bus {:03} Device {:03}", device.bus_number(), device.address());
+        println!("Manufacturer: {}", device.manufacturer().await?);
+        println!("Description: {}", device.description().await?);
+        println!("Serial: {}", device.serial().await?);
+
+        let serial = manager.add_device(device).await?;
+        manager.initialize_device(&serial).await?;
+    }
+
+    serve_devices(manager, config).await
+}
+
+/// `serve --mock-devices N`: serve `N` simulated devices, `MOCK-0` to
+/// `MOCK-{N-1}`, without touching USB.
+#[cfg(feature = "mock")]
+async fn run_mock_server(config: ServerConfig, devices: usize) -> Result<(), Box<dyn Error>> {
+    println!("Quantum Leaks - QRNG Entropy Server (simulated devices)");
+    let manager = DeviceManager::new();
+    for index in 0..devices {
+        // Distinct odd seeds; the mock sets the low bit of any seed
+        let serial = manager.add_mock(&format!("MOCK-{}", index), 2 * index as u64 + 1).await?;
+        println!("Simulated device: {}", serial);
+    }
+    serve_devices(manager, config).await
+}
+
+async fn serve_devices(manager: DeviceManager, config: ServerConfig) -> Result<(), Box<dyn Error>> {
+    println!("\nServing entropy on http://{}", config.bind_addr);
+    // Drain in-flight requests, then release every claimed interface so
+    // devices don't need a replug
+    let result = serve_with_shutdown(manager.clone(), config, shutdown_signal()).await;
+    manager.shutdown_all().await;
+    result?;
+
+    Ok(())
+}
+
+/// Completes on SIGINT (ctrl-c) or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    println!("\nShutting down...");
+}
+
+/// One device in the `scan --json` output.
+#[derive(Debug, Serialize)]
+struct ScanEntry {
+    #[serde(flatten)]
+    info: DeviceInfo,
+    /// Only with `--include-status`; `null` if it couldn't be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<Option<DeviceStatus>>,
+}
+
+/// `scan [--json] [--include-status]`: print each connected device's
+/// descriptor details. Devices whose serial can't be read are skipped with
+/// a warning rather than failing the scan.
+async fn scan(json: bool, include_status: bool) -> Result<(), Box<dyn Error>> {
+    let mut entries = Vec::new();
+    for mut device in scan_devices().await? {
+        let info = match device.info().await {
+            Ok(info) => info,
+            Err(e) => {
+                eprintln!("warning: skipping device at bus {:03} device {:03}: {}", device.bus_number(), device.address(), e);
+                continue;
+            }
+        };
+        let status = if include_status {
+            let status = match device.initialize().await {
+                Ok(()) => device.status().await.ok(),
+                Err(_) => None,
+            };
+            device.shutdown().await;
+            Some(status)
+        } else {
+            None
+        };
+        entries.push(ScanEntry { info, status });
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    println!("Found {} QRNG device(s)", entries.len());
+    for ScanEntry { info, status } in &entries {
+        print!(
+            "{}  {:04x}:{:04x}  USB {}  bus {:03} device {:03}  {} {}",
+            info.serial,
+            info.vendor_id,
+            info.product_id,
+            info.usb_version,
+            info.bus_number,
+            info.address,
+            info.manufacturer.as_deref().unwrap_or("-"),
+            info.description.as_deref().unwrap_or("-"),
+        );
+        match status {
+            Some(Some(status)) => println!("  {:.2} °C  {:.3} V", status.temperature, status.voltage),
+            Some(None) => println!("  status unavailable"),
+            None => println!(),
+        }
+    }
+    Ok(())
+}
+
+/// `inventory [--json]`: list every connected device and exit.
+async fn inventory(json: bool) -> Result<(), Box<dyn Error>> {
+    let manager = DeviceManager::new();
+    for device in scan_devices().await? {
+        manager.add_device(device).await?;
+    }
+    let inventory = manager.inventory().await;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&inventory)?);
+        return Ok(());
+    }
+    for device in &inventory.devices {
+        println!(
+            "{}  {}  {}  firmware {}  {:?}  initialized={}",
+            device.serial,
+            device.description.as_deref().unwrap_or("-"),
+            device.chip.unwrap_or("-"),
+            device.firmware.as_deref().unwrap_or("-"),
+            device.health,
+            device.initialized,
+        );
+    }
+    Ok(())
+}
+
+/// Register every device, initializing `serial` or all of them when it's
+/// `None`.
+async fn open_devices(serial: Option<&str>) -> Result<DeviceManager, Box<dyn Error>> {
+    let manager = DeviceManager::new();
+    for device in scan_devices().await? {
+        let found = manager.add_device(device).await?;
+        if serial.is_none_or(|serial| serial == found) {
+            manager.initialize_device(&found).await?;
+        }
+    }
+    Ok(manager)
+}
+
+/// `serial`, or else the lowest serial `manager` has.
+async fn serial_or_first(manager: &DeviceManager, serial: Option<String>) -> Result<String, Box<dyn Error>> {
+    if let Some(serial) = serial {
+        return Ok(serial);
+    }
+    let mut serials = manager.list_devices().await;
+    serials.sort();
+    Ok(serials.into_iter().next().ok_or("No QRNG devices found")?)
+}
+
+/// `read [--serial S] --bytes N [--out FILE]`: write raw entropy.
+async fn read(serial: Option<String>, bytes: usize, out: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let manager = open_devices(serial.as_deref()).await?;
+    let result = match &serial {
+        Some(serial) => manager.read_entropy(serial, bytes).await,
+        None => manager.read_entropy_any(bytes).await,
+    };
+    manager.shutdown_all().await;
+
+    let entropy = result?;
+    match out {
+        Some(path) => std::fs::write(path, &entropy)?,
+        None => std::io::stdout().lock().write_all(&entropy)?,
+    }
+    Ok(())
+}
+
+/// How often `stream` flushes stdout when the buffer hasn't filled.
+const STREAM_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// `stream [--serial S] [--total-bytes N] [--chunk-size N]`: pipe entropy
+/// to stdout. A closed pipe downstream ends the stream successfully.
+async fn stream(serial: Option<String>, total_bytes: Option<u64>, chunk_size: usize) -> Result<(), Box<dyn Error>> {
+    let manager = open_devices(serial.as_deref()).await?;
+    let device = manager.get_device(&serial_or_first(&manager, serial).await?).await?;
+
+    let mut out = BufWriter::with_capacity(64 * 1024, std::io::stdout().lock());
+    let mut buffer = vec![0u8; chunk_size];
+    let mut remaining = total_bytes.unwrap_or(u64::MAX);
+    let mut flushed = Instant::now();
+    let interrupted = tokio::signal::ctrl_c();
+    tokio::pin!(interrupted);
+
+    let result = loop {
+        if remaining == 0 {
+            break downstream(out.flush());
+        }
+        let want = buffer.len().min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        let n = tokio::select! {
+            n = device.read_entropy_into(&mut buffer[..want]) => match n {
+                Ok(n) => n,
+                Err(e) => break Err(e.into()),
+            },
+            _ = &mut interrupted => break downstream(out.flush()),
+        };
+        remaining -= n as u64;
+        if let Err(e) = out.write_all(&buffer[..n]) {
+            break downstream(Err(e));
+        }
+        if flushed.elapsed() >= STREAM_FLUSH_INTERVAL {
+            if let Err(e) = out.flush() {
+                break downstream(Err(e));
+            }
+            flushed = Instant::now();
+        }
+    };
+    manager.shutdown_all().await;
+    result
+}
+
+/// A write to stdout, treating a reader that has gone away as success.
+fn downstream(result: std::io::Result<()>) -> Result<(), Box<dyn Error>> {
+    match result {
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
     }
+}
 
-    // TODO: Implement API server
-    println!("\nAPI server coming soon...");
+/// `status --serial S`: print status once.
+async fn status(serial: &str) -> Result<(), Box<dyn Error>> {
+    let manager = open_devices(Some(serial)).await?;
+    let result = manager.get_device_status(serial).await;
+    manager.shutdown_all().await;
+    let status = result?;
+    println!("{}  {:.2} °C  {:.3} V", serial, status.temperature, status.voltage);
+    Ok(())
+}
 
+/// `bench [--serial S] [--seconds N]`: read for a while and print the rate.
+async fn bench(serial: Option<String>, duration: Duration) -> Result<(), Box<dyn Error>> {
+    let manager = open_devices(serial.as_deref()).await?;
+    let serial = serial_or_first(&manager, serial).await?;
+    let result = match manager.get_device(&serial).await {
+        Ok(device) => device.benchmark(duration).await,
+        Err(e) => Err(e),
+    };
+    manager.shutdown_all().await;
+    let report = result?;
+    println!(
+        "{}  {:.3} MB/s  ({} bytes in {:.2} s)",
+        serial, report.bytes_per_sec / 1e6, report.bytes, report.elapsed.as_secs_f64()
+    );
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// `watch --serial S [--interval MS]`: print status until ctrl-c.
+async fn watch(serial: &str, interval: Duration) -> Result<(), Box<dyn Error>> {
+    let manager = open_devices(Some(serial)).await?;
+    let mut ticks = tokio::time::interval(interval);
+    let result = loop {
+        tokio::select! {
+            _ = ticks.tick() => {}
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        }
+        match manager.get_device_status(serial).await {
+            Ok(status) => println!("{}  {:.2} °C  {:.3} V", serial, status.temperature, status.voltage),
+            Err(e) => break Err(e),
+        }
+    };
+    manager.shutdown_all().await;
+    Ok(result?)
+}
+
+/// `loadtest [--url U] [--concurrency N] [--sizes A,B] [--seconds S]`:
+/// load a running server and print the report. Fails if the server stopped
+/// responding during the run.
+async fn loadtest(config: &LoadTestConfig, json: bool) -> Result<(), Box<dyn Error>> {
+    eprintln!("Loading {} with {} worker(s) for {:?}...", config.url, config.concurrency, config.duration);
+    let report = run_load_test(config).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "requests: {}  ok: {}  rate limited: {}  errors: {}  stalls: {}  ({:.2}% failed)",
+            report.requests, report.successes, report.rate_limited, report.errors, report.stalls,
+            report.error_rate() * 100.0
+        );
+        println!(
+            "throughput: {:.1} req/s  {:.3} MB/s  ({} bytes in {:.2} s)",
+            report.requests_per_sec(), report.bytes_per_sec() / 1e6, report.bytes, report.elapsed.as_secs_f64()
+        );
+        println!(
+            "latency: p50 {:?}  p90 {:?}  p99 {:?}  max {:?}",
+            report.latency.p50, report.latency.p90, report.latency.p99, report.latency.max
+        );
+    }
+    if report.stalled() {
+        return Err(format!(
+            "server stopped responding: {} stalled request(s), longest gap without a response {:?}",
+            report.stalls, report.longest_gap
+        ).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,134 @@
+//! Entropy Gathering Daemon protocol, as spoken by OpenSSL's `RAND_egd` and
+//! similar clients over a Unix socket.
+//!
+//! Each request starts with a command byte:
+//!
+//! | Command | Request                   | Reply                            |
+//! |---------|---------------------------|----------------------------------|
+//! | `0x00`  |                           | 4-byte big-endian bits available |
+//! | `0x01`  | length                    | count byte, then up to 255 bytes |
+//! | `0x02`  | length                    | exactly `length` bytes           |
+//! | `0x03`  | bits (u16), length, data  | none                             |
+//! | `0x04`  |                           | length byte, then PID as ASCII   |
+
+use feed_me_bits::device::DeviceManager;
+use feed_me_bits::QrngError;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{info, warn};
+
+pub const GET_ENTROPY_LEVEL: u8 = 0x00;
+pub const READ_NONBLOCKING: u8 = 0x01;
+pub const READ_BLOCKING: u8 = 0x02;
+pub const WRITE_ENTROPY: u8 = 0x03;
+pub const GET_PID: u8 = 0x04;
+
+/// Bits reported by `GET_ENTROPY_LEVEL` while a device is attached. The
+/// hardware never runs dry, so this is the largest value the reply can hold.
+pub const ENTROPY_LEVEL_AVAILABLE: u32 = u32::MAX;
+
+/// Accept EGD clients on a Unix socket at `path` until the process exits.
+/// Each connection is served on its own task.
+#[cfg(unix)]
+pub async fn serve_egd(path: impl AsRef<std::path::Path>, manager: DeviceManager) -> Result<(), QrngError> {
+    serve_egd_with_shutdown(path, manager, std::future::pending()).await
+}
+
+/// Like `serve_egd`, but stops accepting clients and removes the socket
+/// once `signal` completes. A socket left at `path` by an earlier run is
+/// replaced; any other file there is an error.
+#[cfg(unix)]
+pub async fn serve_egd_with_shutdown(
+    path: impl AsRef<std::path::Path>,
+    manager: DeviceManager,
+    signal: impl std::future::Future<Output = ()> + Send,
+) -> Result<(), QrngError> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let path = path.as_ref();
+    if std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    info!("Serving EGD on {}", path.display());
+    tokio::pin!(signal);
+    let result = loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => break Err(e.into()),
+            },
+            () = &mut signal => break Ok(()),
+        };
+        let manager = manager.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &manager).await {
+                warn!("Closing EGD connection: {}", e);
+            }
+        });
+    };
+    let _ = std::fs::remove_file(path);
+    result
+}
+
+/// Serve EGD requests on `stream` until the client disconnects. A malformed
+/// request ends the connection with `QrngError::ProtocolError`.
+pub async fn handle_connection<S>(mut stream: S, manager: &DeviceManager) -> Result<(), QrngError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        let command = match stream.read_u8().await {
+            Ok(command) => command,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        match command {
+            GET_ENTROPY_LEVEL => {
+                let level = if manager.list_devices().await.is_empty() { 0 } else { ENTROPY_LEVEL_AVAILABLE };
+                stream.write_u32(level).await?;
+            }
+            READ_NONBLOCKING => {
+                let len = read_request_byte(&mut stream).await?;
+                // A failed read is reported as zero bytes available
+                let entropy = match len {
+                    0 => Vec::new(),
+                    len => manager.read_entropy_any(len as usize).await.unwrap_or_default(),
+                };
+                stream.write_u8(entropy.len() as u8).await?;
+                stream.write_all(&entropy).await?;
+            }
+            READ_BLOCKING => {
+                let len = read_request_byte(&mut stream).await?;
+                if len > 0 {
+                    let entropy = manager.read_entropy_any(len as usize).await?;
+                    stream.write_all(&entropy).await?;
+                }
+            }
+            WRITE_ENTROPY => {
+                // The hardware can't take entropy back, so it is read and dropped
+                let mut header = [0u8; 3];
+                stream.read_exact(&mut header).await.map_err(truncated)?;
+                let mut data = vec![0u8; header[2] as usize];
+                stream.read_exact(&mut data).await.map_err(truncated)?;
+            }
+            GET_PID => {
+                let pid = std::process::id().to_string();
+                stream.write_u8(pid.len() as u8).await?;
+                stream.write_all(pid.as_bytes()).await?;
+            }
+            other => {
+                return Err(QrngError::ProtocolError(format!("Unknown EGD command {:#04x}", other)));
+            }
+        }
+        stream.flush().await?;
+    }
+}
+
+async fn read_request_byte<S: AsyncRead + Unpin>(stream: &mut S) -> Result<u8, QrngError> {
+    stream.read_u8().await.map_err(truncated)
+}
+
+fn truncated(e: std::io::Error) -> QrngError {
+    QrngError::ProtocolError(format!("Truncated EGD request: {}", e))
+}
@@ -0,0 +1,6 @@
+//! Entropy protocols served alongside the HTTP API.
+
+pub mod egd;
+
+#[cfg(test)]
+mod tests;
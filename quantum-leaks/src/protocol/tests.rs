@@ -0,0 +1,112 @@
+#[cfg(test)]
+use super::egd::*;
+use feed_me_bits::device::DeviceManager;
+use feed_me_bits::QrngError;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[tokio::test]
+async fn test_egd_requests_without_devices() {
+    let (mut client, server) = tokio::io::duplex(1024);
+    let task = tokio::spawn(async move { handle_connection(server, &DeviceManager::new()).await });
+
+    client.write_all(&[GET_ENTROPY_LEVEL]).await.unwrap();
+    assert_eq!(client.read_u32().await.unwrap(), 0);
+
+    // Nothing to read: a nonblocking read reports zero bytes
+    client.write_all(&[READ_NONBLOCKING, 32]).await.unwrap();
+    assert_eq!(client.read_u8().await.unwrap(), 0);
+
+    // Written entropy is accepted without a reply
+    client.write_all(&[WRITE_ENTROPY, 0x00, 0x10, 4, 1, 2, 3, 4]).await.unwrap();
+
+    client.write_all(&[GET_PID]).await.unwrap();
+    let len = client.read_u8().await.unwrap();
+    let mut pid = vec![0u8; len as usize];
+    client.read_exact(&mut pid).await.unwrap();
+    assert_eq!(String::from_utf8(pid).unwrap(), std::process::id().to_string());
+
+    // Closing the connection ends the handler cleanly
+    drop(client);
+    task.await.unwrap().expect("Handler failed");
+}
+
+#[tokio::test]
+async fn test_egd_blocking_read_without_devices_fails() {
+    let (mut client, server) = tokio::io::duplex(1024);
+    let task = tokio::spawn(async move { handle_connection(server, &DeviceManager::new()).await });
+
+    client.write_all(&[READ_BLOCKING, 16]).await.unwrap();
+    let result = task.await.unwrap();
+    assert!(matches!(result.unwrap_err(), QrngError::DeviceNotFound(_)));
+}
+
+#[tokio::test]
+async fn test_egd_rejects_malformed_requests() {
+    for request in [&[0x7f][..], &[READ_BLOCKING][..], &[WRITE_ENTROPY, 0x00, 0x10, 4, 1][..]] {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let task = tokio::spawn(async move { handle_connection(server, &DeviceManager::new()).await });
+        client.write_all(request).await.unwrap();
+        drop(client);
+        let result = task.await.unwrap();
+        assert!(matches!(result.unwrap_err(), QrngError::ProtocolError(_)), "{:?}", request);
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_serve_egd_on_unix_socket() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("egd-pool");
+    tokio::spawn(serve_egd(path.clone(), DeviceManager::new()));
+
+    let mut stream = None;
+    for _ in 0..50 {
+        match tokio::net::UnixStream::connect(&path).await {
+            Ok(connected) => {
+                stream = Some(connected);
+                break;
+            }
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+        }
+    }
+    let mut stream = stream.expect("Failed to connect to EGD socket");
+    stream.write_all(&[GET_ENTROPY_LEVEL]).await.unwrap();
+    assert_eq!(stream.read_u32().await.unwrap(), 0);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_serve_egd_replaces_stale_socket() {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("egd-pool");
+    // Left behind by a run that didn't clean up
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(serve_egd_with_shutdown(path.clone(), DeviceManager::new(), async {
+        let _ = stopped.await;
+    }));
+    let mut stream = None;
+    for _ in 0..50 {
+        match tokio::net::UnixStream::connect(&path).await {
+            Ok(connected) => {
+                stream = Some(connected);
+                break;
+            }
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+        }
+    }
+    let mut stream = stream.expect("Failed to connect to EGD socket");
+    stream.write_all(&[GET_ENTROPY_LEVEL]).await.unwrap();
+    assert_eq!(stream.read_u32().await.unwrap(), 0);
+
+    // Stopping removes the socket
+    stop.send(()).unwrap();
+    server.await.unwrap().expect("EGD server failed");
+    assert!(!path.exists());
+
+    // Other files are left alone
+    std::fs::write(&path, b"not a socket").unwrap();
+    assert!(serve_egd(path.clone(), DeviceManager::new()).await.is_err());
+    assert_eq!(std::fs::read(&path).unwrap(), b"not a socket");
+}
@@ -0,0 +1,86 @@
+use std::path::Path;
+use axum::extract::{Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::Response;
+use feed_me_bits::QrngError;
+use super::{ApiError, AppState};
+
+/// Environment variable `ApiKeys::from_env` reads by default.
+pub const API_KEYS_ENV: &str = "QUANTUM_LEAKS_API_KEYS";
+
+/// Bearer tokens accepted by the entropy and device routes. An empty set
+/// leaves the server open.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ApiKeys(Vec<String>);
+
+// Keys stay out of logs
+impl std::fmt::Debug for ApiKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ApiKeys({} keys)", self.0.len())
+    }
+}
+
+impl ApiKeys {
+    pub fn new<I, S>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self(keys.into_iter().map(Into::into).filter(|key: &String| !key.is_empty()).collect())
+    }
+
+    /// Comma-separated keys from the environment variable `var`, e.g.
+    /// `API_KEYS_ENV`. Unset means no keys.
+    pub fn from_env(var: &str) -> Self {
+        std::env::var(var).map_or_else(|_| Self::default(), |keys| Self::new(keys.split(',').map(str::trim)))
+    }
+
+    /// One key per line of `path`. Blank lines and lines starting with `#`
+    /// are skipped.
+    pub fn from_file(path: &Path) -> Result<Self, QrngError> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(Self::new(contents.lines().map(str::trim).filter(|line| !line.starts_with('#'))))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether `candidate` is one of the keys. Every key is compared in full
+    /// so the time taken doesn't reveal how much of a key matched, or which.
+    pub(crate) fn accepts(&self, candidate: &str) -> bool {
+        self.0.iter().fold(false, |found, key| found | constant_time_eq(key.as_bytes(), candidate.as_bytes()))
+    }
+
+    /// Whether a request with this `Authorization` value gets through:
+    /// always when no keys are configured, otherwise only with an accepted
+    /// `Bearer` key.
+    pub(crate) fn admits(&self, authorization: Option<&str>) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| self.accepts(token.trim()))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Reject requests without an accepted `Authorization: Bearer` key with
+/// 401, unless no keys are configured.
+pub(crate) async fn require_api_key(State(state): State<AppState>, request: Request, next: Next) -> Result<Response, ApiError> {
+    let authorization = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !state.config.api_keys.admits(authorization) {
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,206 @@
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use futures::stream::{self, BoxStream, StreamExt};
+use feed_me_bits::device::DeviceManager;
+use feed_me_bits::QrngError;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::service::{Interceptor, Routes};
+use tonic::{Request, Response, Status};
+use tracing::info;
+use super::rate_limit::Rejection;
+use super::stream::{next_frame, stream_chunk};
+use super::{read_entropy, ApiKeys, AppState, Metrics, ServerConfig, UNKNOWN_CLIENT};
+
+use proto::quantum_leaks_server::{QuantumLeaks, QuantumLeaksServer};
+use proto::{
+    Chunk, DeviceList, GetEntropyRequest, GetStatusRequest, ListDevicesRequest, ReadEntropyRequest, StreamEntropyRequest,
+};
+
+/// Messages and client generated from `proto/quantum_leaks.proto`.
+pub mod proto {
+    tonic::include_proto!("quantum_leaks");
+}
+
+/// Largest `Chunk` a `GetEntropy` stream sends (64 KiB). Larger requests
+/// are read and sent a chunk at a time.
+pub const GRPC_CHUNK: usize = 64 * 1024;
+
+/// The gRPC service, backed by `manager`. Shares the REST API's limits:
+/// `max_entropy_bytes`, the rate limits and the API keys, sent as
+/// `authorization: Bearer` metadata.
+pub fn grpc_routes(manager: DeviceManager, config: ServerConfig) -> Routes {
+    routes(AppState::new(manager, None, config, Metrics::new()))
+}
+
+pub(crate) fn routes(state: AppState) -> Routes {
+    let keys = RequireApiKey(state.config.api_keys.clone());
+    Routes::new(QuantumLeaksServer::with_interceptor(GrpcService { state }, keys))
+}
+
+pub(crate) async fn serve_grpc(
+    addr: SocketAddr,
+    routes: Routes,
+    signal: impl Future<Output = ()> + Send,
+) -> Result<(), QrngError> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Serving gRPC on {}", listener.local_addr()?);
+    tonic::transport::Server::builder()
+        .add_routes(routes)
+        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), signal)
+        .await
+        .map_err(|e| QrngError::CommunicationError(format!("gRPC server failed: {}", e)))
+}
+
+#[derive(Clone)]
+struct RequireApiKey(ApiKeys);
+
+impl Interceptor for RequireApiKey {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let authorization = request.metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok());
+        if !self.0.admits(authorization) {
+            return Err(Status::unauthenticated("Missing or invalid API key"));
+        }
+        Ok(request)
+    }
+}
+
+struct GrpcService {
+    state: AppState,
+}
+
+#[tonic::async_trait]
+impl QuantumLeaks for GrpcService {
+    type GetEntropyStream = BoxStream<'static, Result<Chunk, Status>>;
+
+    /// Charged to the rate limits in full up front, like `/entropy`, then
+    /// read lazily so a slow client holds at most one chunk.
+    async fn get_entropy(&self, request: Request<GetEntropyRequest>) -> Result<Response<Self::GetEntropyStream>, Status> {
+        let client = client_ip(&request);
+        let GetEntropyRequest { serial, bytes } = request.into_inner();
+        let bytes = self.admit(client, bytes)?;
+
+        let state = self.state.clone();
+        let chunks = stream::try_unfold(bytes, move |remaining| {
+            let state = state.clone();
+            let serial = serial.clone();
+            async move {
+                if remaining == 0 {
+                    return Ok(None);
+                }
+                let size = remaining.min(GRPC_CHUNK);
+                let data = read_entropy(&state, serial.as_deref(), size).await.map_err(status)?;
+                Ok(Some((Chunk { data }, remaining - size)))
+            }
+        });
+        Ok(Response::new(chunks.boxed()))
+    }
+
+    async fn read_entropy(&self, request: Request<ReadEntropyRequest>) -> Result<Response<Chunk>, Status> {
+        let client = client_ip(&request);
+        let ReadEntropyRequest { serial, size } = request.into_inner();
+        let size = self.admit(client, size)?;
+        let data = read_entropy(&self.state, serial.as_deref(), size).await.map_err(status)?;
+        Ok(Response::new(Chunk { data }))
+    }
+
+    type StreamEntropyStream = BoxStream<'static, Result<Chunk, Status>>;
+
+    async fn stream_entropy(&self, request: Request<StreamEntropyRequest>) -> Result<Response<Self::StreamEntropyStream>, Status> {
+        let client = client_ip(&request);
+        let StreamEntropyRequest { serial, chunk_size } = request.into_inner();
+        let requested = (chunk_size > 0).then(|| usize::try_from(chunk_size).unwrap_or(usize::MAX));
+        let chunk = stream_chunk(&self.state, requested).map_err(Status::invalid_argument)?;
+
+        // Ends after the first failed read, or when the client cancels and
+        // the stream is dropped
+        let state = self.state.clone();
+        let chunks = stream::try_unfold((), move |()| {
+            let state = state.clone();
+            let serial = serial.clone();
+            async move {
+                let data = next_frame(&state, client, serial.as_deref(), chunk).await.map_err(status)?;
+                Ok(Some((Chunk { data }, ())))
+            }
+        });
+        Ok(Response::new(chunks.boxed()))
+    }
+
+    async fn list_devices(&self, _request: Request<ListDevicesRequest>) -> Result<Response<DeviceList>, Status> {
+        let mut serials = self.state.manager.list_devices().await;
+        serials.sort();
+        let mut devices = Vec::with_capacity(serials.len());
+        for serial in &serials {
+            let device = self.state.manager.get_device(serial).await.map_err(status)?;
+            let info = device.info().await.map_err(status)?;
+            devices.push(proto::DeviceInfo {
+                serial: info.serial,
+                vendor_id: info.vendor_id.into(),
+                product_id: info.product_id.into(),
+                manufacturer: info.manufacturer,
+                description: info.description,
+                usb_version: info.usb_version,
+                bus_number: info.bus_number.into(),
+                address: info.address.into(),
+                initialized: info.initialized,
+            });
+        }
+        Ok(Response::new(DeviceList { serials, devices }))
+    }
+
+    async fn get_status(&self, request: Request<GetStatusRequest>) -> Result<Response<proto::Status>, Status> {
+        let serial = request.into_inner().serial;
+        let status = self.state.manager.get_device_status(&serial).await.map_err(status)?;
+        self.state.metrics.record_status(&serial, status.temperature, status.voltage);
+        Ok(Response::new(proto::Status {
+            initialized: status.initialized,
+            temperature: status.temperature,
+            voltage: status.voltage,
+        }))
+    }
+}
+
+impl GrpcService {
+    /// Check a requested size against `max_entropy_bytes` and charge it to
+    /// the rate limits.
+    // Handlers return `Status` by value anyway
+    #[allow(clippy::result_large_err)]
+    fn admit(&self, client: IpAddr, bytes: u64) -> Result<usize, Status> {
+        let max = self.state.config.max_entropy_bytes;
+        let bytes = usize::try_from(bytes).ok()
+            .filter(|&bytes| bytes > 0 && bytes <= max)
+            .ok_or_else(|| Status::invalid_argument(format!("bytes must be between 1 and {}", max)))?;
+        match self.state.limits.admit(client, bytes) {
+            Ok(()) => Ok(bytes),
+            Err(Rejection::RetryAfter(retry_after)) => Err(Status::resource_exhausted(format!(
+                "Rate limit exceeded, retry after {:?}", retry_after
+            ))),
+            Err(Rejection::OverBurst { burst }) => Err(Status::invalid_argument(format!(
+                "bytes exceeds the rate limit burst of {}", burst
+            ))),
+        }
+    }
+}
+
+/// Without a remote address, e.g. over an in-memory channel, all clients
+/// share one rate-limit bucket, as in `client_ip`.
+fn client_ip<T>(request: &Request<T>) -> IpAddr {
+    request.remote_addr().map_or(UNKNOWN_CLIENT, |addr| addr.ip())
+}
+
+// The gRPC counterpart of the REST API's status codes. Requests are checked
+// with `invalid_argument` before they get here, so `InvalidState` is a
+// device fault such as a failed health test.
+fn status(e: QrngError) -> Status {
+    let message = e.to_string();
+    match e {
+        QrngError::DeviceNotFound(_) => Status::not_found(message),
+        QrngError::InvalidState(_)
+        | QrngError::DeviceNotInitialized
+        | QrngError::DeviceDisconnected => Status::unavailable(message),
+        QrngError::Timeout { .. } => Status::deadline_exceeded(message),
+        QrngError::StatusUnsupported => Status::unimplemented(message),
+        _ => Status::internal(message),
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use feed_me_bits::device::DeviceManager;
+use feed_me_bits::health::{HealthMonitor, QualityReport};
+use feed_me_bits::DeviceStatus;
+use serde::Serialize;
+
+/// Settings for the live `/health` check.
+#[derive(Debug, Clone, Copy)]
+pub struct HealthCheckConfig {
+    /// Bytes read from each device per check.
+    pub sample_size: usize,
+    /// Min-entropy per byte the devices are assumed to deliver. The
+    /// repetition-count and adaptive-proportion cutoffs are derived from it,
+    /// so a lower value tolerates more repetition before failing.
+    pub min_entropy: f64,
+    /// A device is sampled at most once per interval. Requests in between
+    /// reuse its last result so health probes can't starve entropy clients.
+    pub min_interval: Duration,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            sample_size: 1024,
+            min_entropy: 6.0,
+            min_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Result of the live health check for one device.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceHealth {
+    pub passed: bool,
+    /// Why the check failed, either the read or a health test.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Body of a `/health` response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub devices: BTreeMap<String, DeviceHealth>,
+}
+
+/// Body of a `/readyz` response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Readiness {
+    /// At least one device is ready.
+    pub ready: bool,
+    /// Devices that are initialized and passed their last health check.
+    pub ready_devices: usize,
+}
+
+impl From<&HealthReport> for Readiness {
+    fn from(report: &HealthReport) -> Self {
+        // Reads from uninitialized devices fail, so they never pass
+        let ready_devices = report.devices.values().filter(|health| health.passed).count();
+        Self { ready: ready_devices > 0, ready_devices }
+    }
+}
+
+/// One device's entry in a `/quality` response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceQuality {
+    /// Of the last health-check sample, in bits per byte. Unset if it
+    /// couldn't be read.
+    pub shannon_entropy: Option<f64>,
+    pub min_entropy: Option<f64>,
+    /// The last health check, as in `/health`.
+    pub health: DeviceHealth,
+    /// Unset if the status couldn't be read.
+    pub temperature: Option<f32>,
+    pub voltage: Option<f32>,
+    /// Bytes read through the manager, health-check samples included.
+    pub bytes_served: u64,
+}
+
+/// Body of a `/quality` response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QualityDashboard {
+    pub devices: BTreeMap<String, DeviceQuality>,
+}
+
+// What one health check learned about a device
+#[derive(Debug, Clone)]
+struct Sample {
+    health: DeviceHealth,
+    quality: Option<QualityReport>,
+    status: Option<DeviceStatus>,
+}
+
+/// Runs the live health check and remembers each device's last result.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HealthChecker {
+    config: HealthCheckConfig,
+    last: Arc<Mutex<HashMap<String, (Instant, Sample)>>>,
+}
+
+impl HealthChecker {
+    pub(crate) fn new(config: HealthCheckConfig) -> Self {
+        Self { config, last: Arc::default() }
+    }
+
+    pub(crate) async fn check(&self, manager: &DeviceManager) -> HealthReport {
+        let mut devices = BTreeMap::new();
+        for serial in manager.list_devices().await {
+            let health = self.sampled(manager, &serial).await.health;
+            devices.insert(serial, health);
+        }
+        HealthReport { healthy: devices.values().all(|health| health.passed), devices }
+    }
+
+    /// The health check's samples, summarized per device. Shares the check's
+    /// per-device rate limit.
+    pub(crate) async fn quality(&self, manager: &DeviceManager) -> QualityDashboard {
+        let mut devices = BTreeMap::new();
+        for serial in manager.list_devices().await {
+            let sample = self.sampled(manager, &serial).await;
+            let bytes_served = manager.stats(&serial).await.map_or(0, |stats| stats.bytes_read);
+            devices.insert(serial, DeviceQuality {
+                shannon_entropy: sample.quality.as_ref().map(|quality| quality.shannon_entropy),
+                min_entropy: sample.quality.as_ref().map(|quality| quality.min_entropy),
+                health: sample.health,
+                temperature: sample.status.as_ref().map(|status| status.temperature),
+                voltage: sample.status.as_ref().map(|status| status.voltage),
+                bytes_served,
+            });
+        }
+        QualityDashboard { devices }
+    }
+
+    // The device's last sample if recent enough, otherwise a fresh one
+    async fn sampled(&self, manager: &DeviceManager, serial: &str) -> Sample {
+        if let Some(sample) = self.recent(serial) {
+            return sample;
+        }
+        let sample = self.sample(manager, serial).await;
+        self.last.lock().unwrap().insert(serial.to_string(), (Instant::now(), sample.clone()));
+        sample
+    }
+
+    fn recent(&self, serial: &str) -> Option<Sample> {
+        let last = self.last.lock().unwrap();
+        last.get(serial)
+            .filter(|(checked, _)| checked.elapsed() < self.config.min_interval)
+            .map(|(_, sample)| sample.clone())
+    }
+
+    async fn sample(&self, manager: &DeviceManager, serial: &str) -> Sample {
+        let (result, quality) = match manager.read_entropy(serial, self.config.sample_size).await {
+            Ok(sample) => {
+                let result = HealthMonitor::for_min_entropy(self.config.min_entropy)
+                    .feed(&sample)
+                    .map_err(|e| e.to_string());
+                (result, Some(QualityReport::measure(&sample)))
+            }
+            Err(e) => (Err(e.to_string()), None),
+        };
+        Sample {
+            health: DeviceHealth { passed: result.is_ok(), error: result.err() },
+            quality,
+            status: manager.get_device_status(serial).await.ok(),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::time::Duration;
+use feed_me_bits::device::DeviceManager;
+use prometheus::{Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// Label used for failures that can't be pinned on one device, e.g. when
+/// no device was available to serve a read.
+pub const UNKNOWN_SERIAL: &str = "unknown";
+
+/// Label for reads served by a custom `EntropySource` rather than a device.
+pub const SOURCE_SERIAL: &str = "source";
+
+/// Prometheus metrics for the entropy server, scraped from `/metrics`.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    bytes_served: IntCounter,
+    device_bytes_served: IntCounterVec,
+    reads: IntCounterVec,
+    read_duration: HistogramVec,
+    read_errors: IntCounterVec,
+    temperature: GaugeVec,
+    voltage: GaugeVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let bytes_served = IntCounter::new("qrng_bytes_served_total", "Entropy bytes served").unwrap();
+        let device_bytes_served = IntCounterVec::new(
+            Opts::new("qrng_device_bytes_served_total", "Entropy bytes served per device"),
+            &["serial"],
+        ).unwrap();
+        let read_duration = HistogramVec::new(
+            HistogramOpts::new("qrng_device_read_duration_seconds", "Time taken by successful entropy reads"),
+            &["serial"],
+        ).unwrap();
+        let reads = IntCounterVec::new(
+            Opts::new("qrng_device_reads_total", "Successful entropy reads per device"),
+            &["serial"],
+        ).unwrap();
+        let read_errors = IntCounterVec::new(
+            Opts::new("qrng_device_read_errors_total", "Failed entropy reads per device"),
+            &["serial"],
+        ).unwrap();
+        let temperature = GaugeVec::new(
+            Opts::new("qrng_device_temperature", "Last reported device temperature"),
+            &["serial"],
+        ).unwrap();
+        let voltage = GaugeVec::new(
+            Opts::new("qrng_device_voltage", "Last reported device voltage"),
+            &["serial"],
+        ).unwrap();
+
+        registry.register(Box::new(bytes_served.clone())).unwrap();
+        registry.register(Box::new(device_bytes_served.clone())).unwrap();
+        registry.register(Box::new(reads.clone())).unwrap();
+        registry.register(Box::new(read_duration.clone())).unwrap();
+        registry.register(Box::new(read_errors.clone())).unwrap();
+        registry.register(Box::new(temperature.clone())).unwrap();
+        registry.register(Box::new(voltage.clone())).unwrap();
+
+        Self { registry, bytes_served, device_bytes_served, reads, read_duration, read_errors, temperature, voltage }
+    }
+
+    pub fn record_read(&self, serial: &str, bytes: usize, elapsed: Duration) {
+        self.reads.with_label_values(&[serial]).inc();
+        self.read_duration.with_label_values(&[serial]).observe(elapsed.as_secs_f64());
+        self.device_bytes_served.with_label_values(&[serial]).inc_by(bytes as u64);
+        self.bytes_served.inc_by(bytes as u64);
+    }
+
+    pub fn record_read_error(&self, serial: &str) {
+        self.read_errors.with_label_values(&[serial]).inc();
+    }
+
+    pub fn record_status(&self, serial: &str, temperature: f32, voltage: f32) {
+        self.temperature.with_label_values(&[serial]).set(temperature as f64);
+        self.voltage.with_label_values(&[serial]).set(voltage as f64);
+    }
+
+    /// Refresh the temperature and voltage gauges of every device in
+    /// `manager` each `interval`. Devices whose status can't be read keep
+    /// their last values.
+    pub fn spawn_status_poller(&self, manager: DeviceManager, interval: Duration) -> JoinHandle<()> {
+        let metrics = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                for serial in manager.list_devices().await {
+                    match manager.get_device_status(&serial).await {
+                        Ok(status) => metrics.record_status(&serial, status.temperature, status.voltage),
+                        Err(e) => debug!("Failed to poll status of {}: {}", serial, e),
+                    }
+                }
+            }
+        })
+    }
+
+    /// All metrics in the Prometheus text exposition format.
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+}
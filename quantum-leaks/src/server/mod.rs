@@ -0,0 +1,420 @@
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::middleware;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use feed_me_bits::device::{DeviceInfo, DeviceManager, Inventory};
+use feed_me_bits::estimate::MinEntropyReport;
+use feed_me_bits::{DeviceStatus, EntropySource, QrngError};
+use serde::Deserialize;
+use futures::FutureExt;
+use tracing::{info, warn};
+
+pub use auth::{ApiKeys, API_KEYS_ENV};
+pub use grpc::{grpc_routes, proto, GRPC_CHUNK};
+pub use health::{DeviceHealth, DeviceQuality, HealthCheckConfig, HealthReport, QualityDashboard, Readiness};
+pub use metrics::Metrics;
+pub use rate_limit::RateLimit;
+pub use stream::{DEFAULT_STREAM_CHUNK, MAX_STREAM_CHUNK};
+pub use tls::{serve_tls, tls_config, TlsConfig};
+
+use health::HealthChecker;
+use rate_limit::Limits;
+
+mod auth;
+mod grpc;
+mod health;
+mod metrics;
+mod rate_limit;
+mod stream;
+mod tls;
+
+/// Address the server listens on unless configured otherwise.
+pub const DEFAULT_BIND_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 8080);
+
+/// Largest `/entropy` request served unless configured otherwise (1 MiB).
+pub const DEFAULT_MAX_ENTROPY_BYTES: usize = 1024 * 1024;
+
+/// How often `serve` refreshes the device status gauges unless configured
+/// otherwise.
+pub const DEFAULT_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub bind_addr: SocketAddr,
+    /// Serve HTTPS only when set, plain HTTP otherwise.
+    pub tls: Option<TlsConfig>,
+    /// Requests for more bytes than this are rejected with 400.
+    pub max_entropy_bytes: usize,
+    /// `serve` polls every device's status this often for the metrics
+    /// gauges. `None` only updates them when `/devices/{serial}/status`
+    /// is requested.
+    pub status_poll_interval: Option<Duration>,
+    /// How `/health` samples devices.
+    pub health: HealthCheckConfig,
+    /// Limit on entropy bytes per client IP, across `/entropy`, `/stream`
+    /// and gRPC. Other keyed routes cost a byte per request. Unlimited when
+    /// `None`.
+    pub rate_limit: Option<RateLimit>,
+    /// Like `rate_limit`, but across all clients together, e.g. the
+    /// devices' combined throughput. Unlimited when `None`.
+    pub global_rate_limit: Option<RateLimit>,
+    /// Keys accepted as `Authorization: Bearer` tokens by the entropy and
+    /// device routes. Empty leaves them open.
+    pub api_keys: ApiKeys,
+    /// Also serve the gRPC API (`grpc_routes`) on this address. It is
+    /// always plaintext, even when `tls` is set.
+    pub grpc_addr: Option<SocketAddr>,
+    /// Also serve the EGD protocol (`protocol::egd`) on a Unix socket at
+    /// this path, for OpenSSL's `RAND_egd` and the like. The socket is
+    /// neither keyed nor rate limited, so restrict it with its permissions.
+    pub egd_socket: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: DEFAULT_BIND_ADDR,
+            tls: None,
+            max_entropy_bytes: DEFAULT_MAX_ENTROPY_BYTES,
+            status_poll_interval: Some(DEFAULT_STATUS_POLL_INTERVAL),
+            health: HealthCheckConfig::default(),
+            rate_limit: None,
+            global_rate_limit: None,
+            api_keys: ApiKeys::default(),
+            grpc_addr: None,
+            egd_socket: None,
+        }
+    }
+}
+
+/// Address charged to the rate limits for clients whose own is unknown.
+const UNKNOWN_CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+
+#[derive(Clone)]
+struct AppState {
+    manager: DeviceManager,
+    /// Serves `/entropy` requests without a serial instead of the manager.
+    source: Option<Arc<dyn EntropySource>>,
+    config: ServerConfig,
+    metrics: Metrics,
+    health: HealthChecker,
+    limits: Limits,
+}
+
+impl AppState {
+    fn new(manager: DeviceManager, source: Option<Arc<dyn EntropySource>>, config: ServerConfig, metrics: Metrics) -> Self {
+        let health = HealthChecker::new(config.health);
+        let limits = Limits::new(config.rate_limit, config.global_rate_limit);
+        Self { manager, source, config, metrics, health, limits }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EntropyQuery {
+    bytes: usize,
+    /// Read from this device instead of the first available one.
+    serial: Option<String>,
+    #[serde(default)]
+    encoding: Encoding,
+}
+
+/// How `/entropy` encodes the bytes in its response body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Encoding {
+    #[default]
+    Raw,
+    Hex,
+    Base64,
+}
+
+impl Encoding {
+    fn encode(self, entropy: Vec<u8>) -> Response {
+        match self {
+            Encoding::Raw => ([(header::CONTENT_TYPE, "application/octet-stream")], entropy).into_response(),
+            Encoding::Hex => {
+                let hex: String = entropy.iter().map(|byte| format!("{:02x}", byte)).collect();
+                ([(header::CONTENT_TYPE, "text/plain")], hex).into_response()
+            }
+            Encoding::Base64 => ([(header::CONTENT_TYPE, "text/plain")], BASE64.encode(entropy)).into_response(),
+        }
+    }
+}
+
+/// A failed request as an HTTP response. `QrngError`s get a status chosen
+/// by variant.
+enum ApiError {
+    Qrng(QrngError),
+    /// The request's parameters are out of bounds.
+    BadRequest(String),
+    /// The client's rate limit is used up for now.
+    RateLimited { retry_after: Duration },
+    /// No accepted API key was presented.
+    Unauthorized,
+}
+
+impl From<QrngError> for ApiError {
+    fn from(e: QrngError) -> Self {
+        Self::Qrng(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let e = match self {
+            Self::Qrng(e) => e,
+            Self::RateLimited { retry_after } => {
+                let seconds = retry_after.as_secs_f64().ceil().max(1.0).to_string();
+                return (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, seconds)], "Rate limit exceeded")
+                    .into_response();
+            }
+            Self::Unauthorized => {
+                return (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")], "Missing or invalid API key")
+                    .into_response();
+            }
+            Self::BadRequest(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+        };
+        let status = match e {
+            QrngError::DeviceNotFound(_) => StatusCode::NOT_FOUND,
+            // Requests are checked before they reach a device, so this is a
+            // device in a bad state, such as one failing its health tests
+            QrngError::InvalidState(_)
+            | QrngError::DeviceNotInitialized
+            | QrngError::DeviceDisconnected => StatusCode::SERVICE_UNAVAILABLE,
+            QrngError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            QrngError::StatusUnsupported => StatusCode::NOT_IMPLEMENTED,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, e.to_string()).into_response()
+    }
+}
+
+/// Routes for the entropy API, backed by `manager`.
+pub fn router(manager: DeviceManager, config: ServerConfig) -> Router {
+    routes(AppState::new(manager, None, config, Metrics::new()))
+}
+
+/// Like `router`, but `/entropy` requests that don't name a serial are
+/// served from `source`, e.g. an `EntropyPool`. Reads from it are labelled
+/// `source` in the metrics.
+pub fn router_with_source(manager: DeviceManager, source: Arc<dyn EntropySource>, config: ServerConfig) -> Router {
+    routes(AppState::new(manager, Some(source), config, Metrics::new()))
+}
+
+// Health and metrics stay open so probes and scrapers need no key, and
+// aren't rate limited. Keys are checked before the rate limits are charged.
+fn routes(state: AppState) -> Router {
+    let protected = Router::new()
+        .route("/entropy", get(entropy))
+        .route("/devices", get(devices))
+        .route("/inventory", get(inventory))
+        .route("/devices/{serial}", get(device_info))
+        .route("/devices/{serial}/status", get(device_status))
+        .route("/devices/{serial}/health", get(device_health))
+        .route("/stream", get(stream::stream))
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit::limit))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::require_api_key));
+    Router::new()
+        .merge(protected)
+        .route("/health", get(health))
+        .route("/quality", get(quality))
+        .route("/healthz", get(liveness))
+        .route("/readyz", get(readiness))
+        .route("/metrics", get(metrics))
+        .with_state(state)
+}
+
+/// Serve the entropy API on `config.bind_addr` until the process exits,
+/// over HTTPS if `config.tls` is set and plain HTTP otherwise.
+pub async fn serve(manager: DeviceManager, config: ServerConfig) -> Result<(), QrngError> {
+    serve_with_shutdown(manager, config, std::future::pending()).await
+}
+
+/// Like `serve`, but once `signal` completes the server stops accepting
+/// connections and returns when in-flight requests have finished. Devices
+/// are left open; call `DeviceManager::shutdown_all` afterwards to release
+/// them.
+pub async fn serve_with_shutdown(
+    manager: DeviceManager,
+    config: ServerConfig,
+    signal: impl Future<Output = ()> + Send + 'static,
+) -> Result<(), QrngError> {
+    let metrics = Metrics::new();
+    let poller = config.status_poll_interval
+        .map(|interval| metrics.spawn_status_poller(manager.clone(), interval));
+    let bind_addr = config.bind_addr;
+    let grpc_addr = config.grpc_addr;
+    let egd_socket = config.egd_socket.clone();
+    let tls = config.tls.as_ref().map(TlsConfig::load).transpose()?;
+    let state = AppState::new(manager, None, config, metrics);
+    let app = routes(state.clone());
+    let signal = signal.shared();
+    if let (Some(grpc_addr), Some(_)) = (grpc_addr, &tls) {
+        warn!("Serving gRPC on {} without TLS", grpc_addr);
+    }
+
+    let http = async {
+        match tls {
+            Some(tls) => tls::serve_tls_on(std::net::TcpListener::bind(bind_addr)?, tls, app, signal.clone()).await,
+            None => serve_http(bind_addr, app, signal.clone()).await,
+        }
+    };
+    let grpc = async {
+        match grpc_addr {
+            Some(grpc_addr) => grpc::serve_grpc(grpc_addr, grpc::routes(state.clone()), signal.clone()).await,
+            None => Ok(()),
+        }
+    };
+    let egd = async {
+        match egd_socket {
+            Some(path) => serve_egd(path, state.manager.clone(), signal.clone()).await,
+            None => Ok(()),
+        }
+    };
+    // The servers share the devices, limits and metrics, and stop together
+    let result = tokio::try_join!(http, grpc, egd).map(|_| ());
+    if let Some(poller) = poller {
+        poller.abort();
+    }
+    result
+}
+
+#[cfg(unix)]
+async fn serve_egd(path: PathBuf, manager: DeviceManager, signal: impl Future<Output = ()> + Send) -> Result<(), QrngError> {
+    crate::protocol::egd::serve_egd_with_shutdown(path, manager, signal).await
+}
+
+#[cfg(not(unix))]
+async fn serve_egd(_path: PathBuf, _manager: DeviceManager, _signal: impl Future<Output = ()> + Send) -> Result<(), QrngError> {
+    Err(QrngError::InvalidState("EGD needs Unix sockets".to_string()))
+}
+
+async fn serve_http(addr: SocketAddr, app: Router, signal: impl Future<Output = ()> + Send + 'static) -> Result<(), QrngError> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Serving entropy on http://{}", listener.local_addr()?);
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(signal)
+        .await?;
+    Ok(())
+}
+
+/// Rate limited by `rate_limit::limit`, which charges the `bytes` asked for.
+async fn entropy(State(state): State<AppState>, Query(query): Query<EntropyQuery>) -> Result<Response, ApiError> {
+    if query.bytes == 0 || query.bytes > state.config.max_entropy_bytes {
+        return Err(ApiError::BadRequest(format!(
+            "bytes must be between 1 and {}", state.config.max_entropy_bytes
+        )));
+    }
+    let entropy = read_entropy(&state, query.serial.as_deref(), query.bytes).await?;
+    Ok(query.encoding.encode(entropy))
+}
+
+/// Read from `serial`, or `read_default` without one, and record the read
+/// in the metrics.
+async fn read_entropy(state: &AppState, serial: Option<&str>, bytes: usize) -> Result<Vec<u8>, QrngError> {
+    let started = Instant::now();
+    let (serial, entropy) = match serial {
+        Some(serial) => {
+            // Unknown serials are not labelled, so clients can't grow the metrics
+            let entropy = state.manager.read_entropy(serial, bytes).await
+                .inspect_err(|e| if !matches!(e, QrngError::DeviceNotFound(_)) {
+                    state.metrics.record_read_error(serial)
+                })?;
+            (serial.to_string(), entropy)
+        }
+        None => read_default(state, bytes).await?,
+    };
+    state.metrics.record_read(&serial, entropy.len(), started.elapsed());
+    Ok(entropy)
+}
+
+/// Read from the configured source, or else any device, returning the
+/// serial to label the read with. Failures are counted in the metrics.
+async fn read_default(state: &AppState, bytes: usize) -> Result<(String, Vec<u8>), QrngError> {
+    match &state.source {
+        Some(source) => {
+            let entropy = source.read(bytes).await
+                .inspect_err(|_| state.metrics.record_read_error(metrics::SOURCE_SERIAL))?;
+            Ok((metrics::SOURCE_SERIAL.to_string(), entropy))
+        }
+        None => state.manager.read_entropy_from_any(bytes).await
+            .inspect_err(|_| state.metrics.record_read_error(metrics::UNKNOWN_SERIAL)),
+    }
+}
+
+/// Without connection info (e.g. a router used directly) all clients
+/// share the unspecified address, and so one rate-limit bucket.
+fn client_ip(client: Option<Extension<ConnectInfo<SocketAddr>>>) -> IpAddr {
+    client.map_or(UNKNOWN_CLIENT, |Extension(ConnectInfo(addr))| addr.ip())
+}
+
+async fn devices(State(state): State<AppState>) -> Json<Vec<String>> {
+    let mut serials = state.manager.list_devices().await;
+    serials.sort();
+    Json(serials)
+}
+
+/// Every device with its firmware, chip, health and config, as
+/// `inventory --json` prints it.
+async fn inventory(State(state): State<AppState>) -> Json<Inventory> {
+    Json(state.manager.inventory().await)
+}
+
+async fn device_info(State(state): State<AppState>, Path(serial): Path<String>) -> Result<Json<DeviceInfo>, ApiError> {
+    let device = state.manager.get_device(&serial).await?;
+    Ok(Json(device.info().await?))
+}
+
+async fn device_status(State(state): State<AppState>, Path(serial): Path<String>) -> Result<Json<DeviceStatus>, ApiError> {
+    let status = state.manager.get_device_status(&serial).await?;
+    state.metrics.record_status(&serial, status.temperature, status.voltage);
+    Ok(Json(status))
+}
+
+/// SP 800-90B min-entropy of a fresh `health.sample_size` sample from
+/// `serial`, with each estimator's result and the confidence level used.
+async fn device_health(State(state): State<AppState>, Path(serial): Path<String>) -> Result<Json<MinEntropyReport>, ApiError> {
+    Ok(Json(state.manager.min_entropy_report(&serial, state.config.health.sample_size).await?))
+}
+
+/// Live SP 800-90B check of every device: 200 if all pass, 503 otherwise.
+async fn health(State(state): State<AppState>) -> (StatusCode, Json<HealthReport>) {
+    let report = state.health.check(&state.manager).await;
+    let status = if report.healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+/// Entropy estimates, last health check, status and bytes served for every
+/// device, from samples shared with `/health`.
+async fn quality(State(state): State<AppState>) -> Json<QualityDashboard> {
+    Json(state.health.quality(&state.manager).await)
+}
+
+/// The process is up. Says nothing about the devices; see `/readyz`.
+async fn liveness() -> &'static str {
+    "ok"
+}
+
+/// 200 while at least one device can serve entropy, 503 otherwise. Shares
+/// the `/health` check and its per-device rate limit.
+async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<Readiness>) {
+    let readiness = Readiness::from(&state.health.check(&state.manager).await);
+    let status = if readiness.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(readiness))
+}
+
+async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], state.metrics.encode())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,177 @@
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use axum::extract::{ConnectInfo, Query, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::Extension;
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultDirectRateLimiter, DefaultKeyedRateLimiter, InsufficientCapacity, NotUntil, Quota};
+use serde::Deserialize;
+use super::{client_ip, ApiError, AppState};
+
+/// How often `Limits` forgets clients whose buckets have refilled.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Per-client allowance for `/entropy`, as a token bucket counted in bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Rate at which a client's allowance refills. Rates under one byte per
+    /// second, including zero, negative and NaN rates, are raised to it.
+    pub bytes_per_second: f64,
+    /// Most a client can save up, and so the largest single request it
+    /// can make. Capped at `u32::MAX`.
+    pub burst: usize,
+}
+
+impl RateLimit {
+    fn burst(&self) -> usize {
+        self.burst.min(u32::MAX as usize)
+    }
+
+    fn quota(&self) -> Quota {
+        // `max` also maps NaN to the floor, and the floor keeps the bucket's
+        // refill time within what governor can represent
+        let per_byte = Duration::from_secs_f64(1.0 / self.bytes_per_second.max(1.0)).max(Duration::from_nanos(1));
+        let burst = NonZeroU32::new(self.burst() as u32).unwrap_or(NonZeroU32::MIN);
+        Quota::with_period(per_byte).expect("period is at least 1 ns").allow_burst(burst)
+    }
+}
+
+/// Why `Limits::admit` turned a request away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Rejection {
+    /// The bucket will hold enough after this long.
+    RetryAfter(Duration),
+    /// The request is larger than the bucket can ever hold.
+    OverBurst { burst: usize },
+}
+
+struct ClientLimiter {
+    limiter: DefaultKeyedRateLimiter<IpAddr>,
+    burst: usize,
+    pruned: Mutex<Instant>,
+}
+
+struct GlobalLimiter {
+    limiter: DefaultDirectRateLimiter,
+    burst: usize,
+}
+
+/// The per-client and global `governor` limiters, shared by the `limit`
+/// middleware, `/stream` and the gRPC service. Clients idle long enough
+/// to have a full bucket again are dropped every `PRUNE_INTERVAL`, as a
+/// new client starts with a full bucket anyway.
+#[derive(Clone, Default)]
+pub(crate) struct Limits {
+    clients: Option<Arc<ClientLimiter>>,
+    global: Option<Arc<GlobalLimiter>>,
+}
+
+impl Limits {
+    pub(crate) fn new(client: Option<RateLimit>, global: Option<RateLimit>) -> Self {
+        let clients = client.map(|limit| Arc::new(ClientLimiter {
+            limiter: DefaultKeyedRateLimiter::keyed(limit.quota()),
+            burst: limit.burst(),
+            pruned: Mutex::new(Instant::now()),
+        }));
+        let global = global.map(|limit| Arc::new(GlobalLimiter {
+            limiter: DefaultDirectRateLimiter::direct(limit.quota()),
+            burst: limit.burst(),
+        }));
+        Self { clients, global }
+    }
+
+    /// Largest request the configured limits could ever admit.
+    pub(crate) fn max_burst(&self) -> Option<usize> {
+        let clients = self.clients.as_ref().map(|clients| clients.burst);
+        let global = self.global.as_ref().map(|global| global.burst);
+        clients.into_iter().chain(global).min()
+    }
+
+    /// Charge `bytes` to `client`'s budget, then to the global one. A
+    /// global rejection doesn't refund the client, as governor can't give
+    /// cells back.
+    pub(crate) fn admit(&self, client: IpAddr, bytes: usize) -> Result<(), Rejection> {
+        if let Some(burst) = self.max_burst().filter(|&burst| bytes > burst) {
+            return Err(Rejection::OverBurst { burst });
+        }
+        // Within the burst, and so within u32
+        let Some(cells) = NonZeroU32::new(bytes.min(u32::MAX as usize) as u32) else {
+            return Ok(());
+        };
+        if let Some(clients) = &self.clients {
+            self.prune_if_due(clients);
+            verdict(clients.limiter.check_key_n(&client, cells), clients.limiter.clock())?;
+        }
+        if let Some(global) = &self.global {
+            verdict(global.limiter.check_n(cells), global.limiter.clock())?;
+        }
+        Ok(())
+    }
+
+    fn prune_if_due(&self, clients: &ClientLimiter) {
+        let mut pruned = clients.pruned.lock().unwrap();
+        if pruned.elapsed() >= PRUNE_INTERVAL {
+            *pruned = Instant::now();
+            self.prune();
+        }
+    }
+
+    /// Forget every client whose bucket has refilled.
+    pub(super) fn prune(&self) {
+        if let Some(clients) = &self.clients {
+            clients.limiter.retain_recent();
+            clients.limiter.shrink_to_fit();
+        }
+    }
+
+    /// Clients with a bucket, i.e. seen since they last refilled.
+    #[cfg(test)]
+    pub(crate) fn clients(&self) -> usize {
+        self.clients.as_ref().map_or(0, |clients| clients.limiter.len())
+    }
+}
+
+fn verdict(
+    outcome: Result<Result<(), NotUntil<<DefaultClock as Clock>::Instant>>, InsufficientCapacity>,
+    clock: &DefaultClock,
+) -> Result<(), Rejection> {
+    match outcome {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(not_until)) => Err(Rejection::RetryAfter(not_until.wait_time_from(clock.now()))),
+        // `admit` checks the burst first
+        Err(InsufficientCapacity(burst)) => Err(Rejection::OverBurst { burst: burst as usize }),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Cost {
+    bytes: Option<usize>,
+}
+
+/// Charge each request to the limits before it reaches its handler: the
+/// entropy it asks for in a `bytes` query parameter, or one byte without
+/// one. A `bytes` over `max_entropy_bytes`, which the handler refuses,
+/// also costs one byte. Turned-away requests get 429 with `Retry-After`,
+/// or 400 if they could never fit the burst.
+pub(crate) async fn limit(
+    State(state): State<AppState>,
+    client: Option<Extension<ConnectInfo<SocketAddr>>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let bytes = Query::<Cost>::try_from_uri(request.uri()).ok()
+        .and_then(|Query(cost)| cost.bytes)
+        .filter(|&bytes| bytes <= state.config.max_entropy_bytes)
+        .unwrap_or(1)
+        .max(1);
+    match state.limits.admit(client_ip(client), bytes) {
+        Ok(()) => Ok(next.run(request).await),
+        Err(Rejection::RetryAfter(retry_after)) => Err(ApiError::RateLimited { retry_after }),
+        Err(Rejection::OverBurst { burst }) => Err(ApiError::BadRequest(format!(
+            "bytes exceeds the rate limit burst of {}", burst
+        ))),
+    }
+}
@@ -0,0 +1,86 @@
+use std::net::{IpAddr, SocketAddr};
+use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code};
+use axum::extract::{ConnectInfo, Query, State};
+use axum::response::Response;
+use axum::Extension;
+use feed_me_bits::QrngError;
+use serde::Deserialize;
+use tracing::{debug, warn};
+use super::rate_limit::Rejection;
+use super::{client_ip, read_entropy, ApiError, AppState};
+
+/// Bytes per `/stream` frame unless the client asks otherwise.
+pub const DEFAULT_STREAM_CHUNK: usize = 1024;
+
+/// Largest `/stream` frame a client can ask for (64 KiB), further capped
+/// by `max_entropy_bytes`.
+pub const MAX_STREAM_CHUNK: usize = 64 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct StreamQuery {
+    chunk: Option<usize>,
+}
+
+/// `GET /stream`: upgrade to a WebSocket and send binary frames of entropy
+/// from the same source as `/entropy` until the client goes away. Frames
+/// count against the client's rate limit; the stream waits for its bucket
+/// to refill rather than failing.
+pub(crate) async fn stream(
+    State(state): State<AppState>,
+    client: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Query(query): Query<StreamQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let chunk = stream_chunk(&state, query.chunk).map_err(ApiError::BadRequest)?;
+    let ip = client_ip(client);
+    Ok(upgrade.on_upgrade(move |socket| send_entropy(socket, state, ip, chunk)))
+}
+
+/// The frame size for a stream asking for `requested` bytes per frame, or
+/// why it is out of bounds or could never pass the rate limit.
+pub(crate) fn stream_chunk(state: &AppState, requested: Option<usize>) -> Result<usize, String> {
+    let max_chunk = MAX_STREAM_CHUNK.min(state.config.max_entropy_bytes);
+    let chunk = requested.unwrap_or(DEFAULT_STREAM_CHUNK.min(max_chunk));
+    if chunk == 0 || chunk > max_chunk {
+        return Err(format!("chunk must be between 1 and {}", max_chunk));
+    }
+    if let Some(burst) = state.limits.max_burst().filter(|&burst| chunk > burst) {
+        return Err(format!("chunk exceeds the rate limit burst of {}", burst));
+    }
+    Ok(chunk)
+}
+
+async fn send_entropy(mut socket: WebSocket, state: AppState, ip: IpAddr, chunk: usize) {
+    loop {
+        // Pings are answered by the socket while it is polled here
+        let frame = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            frame = next_frame(&state, ip, None, chunk) => frame,
+        };
+        let entropy = match frame {
+            Ok(entropy) => entropy,
+            Err(e) => {
+                warn!("Ending entropy stream: {}", e);
+                let reason = CloseFrame { code: close_code::ERROR, reason: e.to_string().into() };
+                let _ = socket.send(Message::Close(Some(reason))).await;
+                break;
+            }
+        };
+        if socket.send(Message::Binary(entropy.into())).await.is_err() {
+            break;
+        }
+    }
+    debug!("Entropy stream to {} closed", ip);
+}
+
+/// Wait until `ip` may read `chunk` bytes, then read them from `serial` or
+/// the default source. The chunk must have passed `stream_chunk`.
+pub(crate) async fn next_frame(state: &AppState, ip: IpAddr, serial: Option<&str>, chunk: usize) -> Result<Vec<u8>, QrngError> {
+    while let Err(Rejection::RetryAfter(retry_after)) = state.limits.admit(ip, chunk) {
+        tokio::time::sleep(retry_after).await;
+    }
+    read_entropy(state, serial, chunk).await
+}
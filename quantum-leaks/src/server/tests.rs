@@ -0,0 +1,874 @@
+#[cfg(test)]
+use super::*;
+use axum::body::{to_bytes, Body};
+use axum::http::Request;
+use std::sync::Arc;
+use std::time::Duration;
+use feed_me_bits::device::MockQrngDevice;
+use feed_me_bits::health::HealthMonitor;
+use feed_me_bits::source::BoxFuture;
+use super::rate_limit::Rejection;
+use tower::ServiceExt;
+
+async fn get(app: Router, uri: &str) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.expect("Failed to send request");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.expect("Failed to read body");
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_devices_lists_serials() {
+    let app = router(DeviceManager::new(), ServerConfig::default());
+    let (status, body) = get(app, "/devices").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, b"[]");
+}
+
+#[tokio::test]
+async fn test_entropy_rejects_bad_sizes() {
+    let config = ServerConfig { max_entropy_bytes: 64, ..ServerConfig::default() };
+    for uri in ["/entropy?bytes=65", "/entropy?bytes=0", "/entropy?bytes=lots", "/entropy"] {
+        let (status, _) = get(router(DeviceManager::new(), config.clone()), uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+    }
+
+    // The default cap is 1 MiB
+    let app = router(DeviceManager::new(), ServerConfig::default());
+    let (status, _) = get(app, "/entropy?bytes=1048577").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_entropy_without_devices() {
+    let app = router(DeviceManager::new(), ServerConfig::default());
+    let (status, _) = get(app, "/entropy?bytes=32").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_entropy_from_unknown_device() {
+    let app = router(DeviceManager::new(), ServerConfig::default());
+    let (status, body) = get(app, "/entropy?bytes=32&serial=NOPE").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(String::from_utf8(body).unwrap().contains("NOPE"));
+}
+
+// A manager whose only device, MOCK-A, fails its health tests on every read
+async fn failing_health_manager() -> DeviceManager {
+    let manager = DeviceManager::new();
+    let mut device = MockQrngDevice::new("MOCK-A", 1).device();
+    device.initialize().await.expect("Failed to initialize device");
+    device.set_health_monitor(HealthMonitor::new(1, 512, 512));
+    manager.add_device(device).await.expect("Failed to add device");
+    manager
+}
+
+#[tokio::test]
+async fn test_entropy_from_unhealthy_device() {
+    // The device is at fault, not the request
+    let app = router(failing_health_manager().await, ServerConfig::default());
+    let (status, body) = get(app, "/entropy?bytes=32&serial=MOCK-A").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(String::from_utf8(body).unwrap().contains("Repetition"), "health failure not reported");
+}
+
+#[tokio::test]
+async fn test_serve_stops_on_shutdown_signal() {
+    let config = ServerConfig { bind_addr: "127.0.0.1:0".parse().unwrap(), ..ServerConfig::default() };
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(serve_with_shutdown(DeviceManager::new(), config, async {
+        let _ = stopped.await;
+    }));
+
+    stop.send(()).unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(5), server).await
+        .expect("Server didn't stop after the shutdown signal");
+    result.unwrap().expect("Server failed");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_serve_starts_egd_socket() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let path = dir.path().join("egd-pool");
+    let manager = DeviceManager::new();
+    manager.add_mock("MOCK-A", 1).await.unwrap();
+    let config = ServerConfig {
+        bind_addr: "127.0.0.1:0".parse().unwrap(),
+        egd_socket: Some(path.clone()),
+        ..ServerConfig::default()
+    };
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(serve_with_shutdown(manager, config, async {
+        let _ = stopped.await;
+    }));
+
+    let mut stream = None;
+    for _ in 0..50 {
+        if let Ok(connected) = tokio::net::UnixStream::connect(&path).await {
+            stream = Some(connected);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let mut stream = stream.expect("Failed to connect to EGD socket");
+    stream.write_all(&[crate::protocol::egd::READ_BLOCKING, 16]).await.unwrap();
+    let mut entropy = [0u8; 16];
+    stream.read_exact(&mut entropy).await.expect("Failed to read entropy over EGD");
+
+    // Stops with the HTTP server
+    stop.send(()).unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(5), server).await
+        .expect("Servers didn't stop after the shutdown signal");
+    result.unwrap().expect("Server failed");
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_rate_limit_bucket_refills() {
+    let limits = Limits::new(Some(RateLimit { bytes_per_second: 1000.0, burst: 200 }), None);
+    let client = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    assert_eq!(limits.max_burst(), Some(200));
+    assert_eq!(limits.admit(client, 200), Ok(()));
+    let Err(Rejection::RetryAfter(retry_after)) = limits.admit(client, 50) else {
+        panic!("Drained bucket admitted a request");
+    };
+    assert!(retry_after > Duration::from_millis(40) && retry_after <= Duration::from_millis(50), "{:?}", retry_after);
+    std::thread::sleep(retry_after);
+    assert_eq!(limits.admit(client, 50), Ok(()));
+    assert_eq!(limits.admit(client, 201), Err(Rejection::OverBurst { burst: 200 }));
+
+    // Other clients have buckets of their own
+    assert_eq!(limits.admit(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), 200), Ok(()));
+}
+
+#[test]
+fn test_rate_limit_forgets_refilled_clients() {
+    let limits = Limits::new(Some(RateLimit { bytes_per_second: 1000.0, burst: 100 }), None);
+    for last in 0..50 {
+        assert_eq!(limits.admit(IpAddr::V4(Ipv4Addr::new(10, 0, 0, last)), 1), Ok(()));
+    }
+    // Drained far enough that it won't have refilled by the prune
+    assert_eq!(limits.admit(IpAddr::V4(Ipv4Addr::LOCALHOST), 100), Ok(()));
+    assert_eq!(limits.clients(), 51);
+
+    std::thread::sleep(Duration::from_millis(10));
+    limits.prune();
+    assert_eq!(limits.clients(), 1);
+    assert!(matches!(limits.admit(IpAddr::V4(Ipv4Addr::LOCALHOST), 100), Err(Rejection::RetryAfter(_))));
+}
+
+#[test]
+fn test_rate_limit_tolerates_bad_rates() {
+    let client = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    for bytes_per_second in [0.0, -1.0, f64::NAN, f64::MIN_POSITIVE] {
+        let limits = Limits::new(Some(RateLimit { bytes_per_second, burst: 8 }), None);
+        assert_eq!(limits.admit(client, 8), Ok(()));
+        assert!(matches!(limits.admit(client, 8), Err(Rejection::RetryAfter(_))), "{}", bytes_per_second);
+    }
+    let limits = Limits::new(Some(RateLimit { bytes_per_second: f64::INFINITY, burst: 8 }), None);
+    assert_eq!(limits.admit(client, 8), Ok(()));
+    let limits = Limits::new(None, Some(RateLimit { bytes_per_second: 1.0, burst: 0 }));
+    assert_eq!(limits.admit(client, 1), Err(Rejection::OverBurst { burst: 0 }));
+}
+
+#[tokio::test]
+async fn test_entropy_rate_limited_per_client() {
+    let config = ServerConfig {
+        rate_limit: Some(RateLimit { bytes_per_second: 1.0, burst: 64 }),
+        ..ServerConfig::default()
+    };
+    let app = router_with_source(DeviceManager::new(), Arc::new(FixedSource(0x5a)), config);
+    let request = |ip: [u8; 4]| {
+        Request::builder()
+            .uri("/entropy?bytes=32")
+            .extension(ConnectInfo(SocketAddr::from((ip, 40000))))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    for _ in 0..2 {
+        let response = app.clone().oneshot(request([10, 0, 0, 1])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let response = app.clone().oneshot(request([10, 0, 0, 1])).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "32");
+
+    // Another client is unaffected
+    let response = app.clone().oneshot(request([10, 0, 0, 2])).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // A request over the burst could never succeed
+    let (status, _) = get(app, "/entropy?bytes=65").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_rate_limit_spares_oversized_requests() {
+    let config = ServerConfig {
+        max_entropy_bytes: 32,
+        rate_limit: Some(RateLimit { bytes_per_second: 1.0, burst: 64 }),
+        ..ServerConfig::default()
+    };
+    let app = router_with_source(DeviceManager::new(), Arc::new(FixedSource(0x5a)), config);
+
+    // Refused by the handler, so only charged a byte
+    let (status, _) = get(app.clone(), "/entropy?bytes=60").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    for _ in 0..2 {
+        let (status, _) = get(app.clone(), "/entropy?bytes=31").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, _) = get(app, "/entropy?bytes=2").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_entropy_rate_limited_globally() {
+    let config = ServerConfig {
+        rate_limit: Some(RateLimit { bytes_per_second: 1.0, burst: 64 }),
+        global_rate_limit: Some(RateLimit { bytes_per_second: 1.0, burst: 96 }),
+        ..ServerConfig::default()
+    };
+    let app = router_with_source(DeviceManager::new(), Arc::new(FixedSource(0x5a)), config);
+    let request = |ip: [u8; 4], bytes: usize| {
+        Request::builder()
+            .uri(format!("/entropy?bytes={}", bytes))
+            .extension(ConnectInfo(SocketAddr::from((ip, 40000))))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // Two clients within their own budgets share the global one
+    for ip in [[10, 0, 0, 1], [10, 0, 0, 2]] {
+        let response = app.clone().oneshot(request(ip, 48)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    let response = app.clone().oneshot(request([10, 0, 0, 3], 32)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "32");
+}
+
+#[test]
+fn test_limits_charge_client_and_global_budgets() {
+    let config = ServerConfig {
+        rate_limit: Some(RateLimit { bytes_per_second: 1.0, burst: 64 }),
+        global_rate_limit: Some(RateLimit { bytes_per_second: 1.0, burst: 96 }),
+        ..ServerConfig::default()
+    };
+    let state = AppState::new(DeviceManager::new(), None, config, Metrics::new());
+    let client = |last| IpAddr::V4(Ipv4Addr::new(10, 0, 0, last));
+    assert_eq!(state.limits.max_burst(), Some(64));
+    assert_eq!(state.limits.admit(client(1), 64), Ok(()));
+
+    // The first client's own budget is spent, the global one has 32 left
+    assert!(matches!(state.limits.admit(client(1), 1), Err(Rejection::RetryAfter(_))));
+    assert_eq!(state.limits.admit(client(2), 32), Ok(()));
+    assert!(matches!(state.limits.admit(client(3), 1), Err(Rejection::RetryAfter(_))));
+}
+
+#[tokio::test]
+async fn test_rate_limit_covers_every_protected_route() {
+    let config = ServerConfig {
+        rate_limit: Some(RateLimit { bytes_per_second: 1.0, burst: 2 }),
+        ..ServerConfig::default()
+    };
+    let app = router(DeviceManager::new(), config);
+
+    // Requests without a size cost a byte each
+    for _ in 0..2 {
+        let (status, _) = get(app.clone(), "/devices").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, _) = get(app.clone(), "/devices").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    // Probes stay open
+    let (status, _) = get(app, "/healthz").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_api_key_auth() {
+    let config = ServerConfig { api_keys: ApiKeys::new(["first-key", "second-key"]), ..ServerConfig::default() };
+    let app = router_with_source(DeviceManager::new(), Arc::new(FixedSource(0x5a)), config);
+    let request = |authorization: Option<&str>| {
+        let builder = Request::builder().uri("/entropy?bytes=16");
+        let builder = match authorization {
+            Some(value) => builder.header(header::AUTHORIZATION, value),
+            None => builder,
+        };
+        builder.body(Body::empty()).unwrap()
+    };
+
+    for key in ["first-key", "second-key"] {
+        let response = app.clone().oneshot(request(Some(&format!("Bearer {}", key)))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    for authorization in [None, Some("Bearer wrong-key"), Some("Bearer first-ke"), Some("first-key"), Some("Basic Zmlyc3Qta2V5")] {
+        let response = app.clone().oneshot(request(authorization)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{:?}", authorization);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+    }
+
+    // Device routes are protected too, probes are not
+    let (status, _) = get(app.clone(), "/devices").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = get(app, "/healthz").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[test]
+fn test_api_keys_from_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("keys");
+    std::fs::write(&path, "# clients\nfirst-key\n\n  second-key  \n").unwrap();
+    assert_eq!(ApiKeys::from_file(&path).unwrap(), ApiKeys::new(["first-key", "second-key"]));
+    assert!(ApiKeys::new([""]).is_empty());
+}
+
+#[tokio::test]
+async fn test_health_without_devices() {
+    let app = router(DeviceManager::new(), ServerConfig::default());
+    let (status, body) = get(app, "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    let report: serde_json::Value = serde_json::from_slice(&body).expect("Failed to parse health report");
+    assert_eq!(report, serde_json::json!({ "healthy": true, "devices": {} }));
+}
+
+#[tokio::test]
+async fn test_liveness_and_readiness_without_devices() {
+    let app = router(DeviceManager::new(), ServerConfig::default());
+    let (status, body) = get(app.clone(), "/healthz").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, b"ok");
+
+    // Alive but with nothing to serve
+    let (status, body) = get(app, "/readyz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let readiness: serde_json::Value = serde_json::from_slice(&body).expect("Failed to parse readiness");
+    assert_eq!(readiness, serde_json::json!({ "ready": false, "ready_devices": 0 }));
+}
+
+#[test]
+fn test_readiness_counts_passing_devices() {
+    let health = |passed| DeviceHealth { passed, error: (!passed).then(|| "Device not initialized".to_string()) };
+    let report = HealthReport {
+        healthy: false,
+        devices: [("A".to_string(), health(true)), ("B".to_string(), health(false)), ("C".to_string(), health(true))]
+            .into_iter()
+            .collect(),
+    };
+    assert_eq!(Readiness::from(&report), Readiness { ready: true, ready_devices: 2 });
+
+    let report = HealthReport { healthy: false, devices: [("B".to_string(), health(false))].into_iter().collect() };
+    assert_eq!(Readiness::from(&report), Readiness { ready: false, ready_devices: 0 });
+}
+
+#[tokio::test]
+async fn test_quality_has_an_entry_per_device() {
+    let manager = DeviceManager::new();
+    manager.add_mock("MOCK-A", 1).await.unwrap();
+    manager.add_mock("MOCK-B", 2).await.unwrap();
+    let app = router(manager, ServerConfig::default());
+    let (status, body) = get(app.clone(), "/quality").await;
+    assert_eq!(status, StatusCode::OK);
+    let dashboard: serde_json::Value = serde_json::from_slice(&body).expect("Failed to parse quality dashboard");
+    let devices = dashboard["devices"].as_object().expect("No devices in the dashboard");
+    assert_eq!(devices.keys().collect::<Vec<_>>(), ["MOCK-A", "MOCK-B"]);
+    for quality in devices.values() {
+        assert!(quality["shannon_entropy"].as_f64().unwrap() > 7.0, "{}", quality);
+        assert_eq!(quality["health"]["passed"], true);
+        assert_eq!(quality["bytes_served"], HealthCheckConfig::default().sample_size);
+    }
+
+    // Samples are shared with /health and reused within its interval
+    let (status, _) = get(app.clone(), "/health").await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, again) = get(app, "/quality").await;
+    assert_eq!(again, body);
+}
+
+#[tokio::test]
+async fn test_inventory_lists_every_device() {
+    let manager = DeviceManager::new();
+    manager.add_mock("MOCK-B", 2).await.unwrap();
+    manager.add_mock("MOCK-A", 1).await.unwrap();
+    let app = router(manager, ServerConfig::default());
+    let (status, body) = get(app, "/inventory").await;
+    assert_eq!(status, StatusCode::OK);
+    let inventory: serde_json::Value = serde_json::from_slice(&body).expect("Failed to parse inventory");
+    let devices = inventory["devices"].as_array().expect("No devices in the inventory");
+    let serials: Vec<_> = devices.iter().map(|device| device["serial"].as_str().unwrap()).collect();
+    assert_eq!(serials, ["MOCK-A", "MOCK-B"]);
+    for device in devices {
+        assert_eq!(device["initialized"], true);
+        assert_eq!(device["health"], "healthy");
+        assert_eq!(device["firmware"], "6.0");
+        assert_eq!(device["chip"], "FT232R");
+        assert_eq!(device["config"]["entropy_endpoint"], 0x81);
+    }
+}
+
+#[tokio::test]
+async fn test_device_health_reports_min_entropy() {
+    let manager = DeviceManager::new();
+    manager.add_mock("MOCK-A", 1).await.unwrap();
+    let app = router(manager, ServerConfig::default());
+    let (status, body) = get(app.clone(), "/devices/MOCK-A/health").await;
+    assert_eq!(status, StatusCode::OK);
+    let report: serde_json::Value = serde_json::from_slice(&body).expect("Failed to parse health report");
+    assert_eq!(report["sample_size"], HealthCheckConfig::default().sample_size);
+    assert_eq!(report["confidence_level"], 0.99);
+
+    // The reported value is the minimum across the estimators
+    let estimates = report["estimates"].as_array().expect("No estimates in the report");
+    let names: Vec<_> = estimates.iter().map(|estimate| estimate["estimator"].as_str().unwrap()).collect();
+    assert_eq!(names, ["most_common_value", "collision", "markov"]);
+    let lowest = estimates.iter().map(|estimate| estimate["min_entropy"].as_f64().unwrap()).fold(f64::INFINITY, f64::min);
+    assert_eq!(report["min_entropy"].as_f64(), Some(lowest));
+    assert!(lowest > 5.0, "mock scored {}", lowest);
+
+    let (status, _) = get(app, "/devices/NOPE/health").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_info_for_unknown_device() {
+    let app = router(DeviceManager::new(), ServerConfig::default());
+    let (status, body) = get(app, "/devices/NOPE").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(String::from_utf8(body).unwrap().contains("NOPE"));
+}
+
+#[derive(Debug)]
+struct FixedSource(u8);
+
+impl EntropySource for FixedSource {
+    fn read(&self, size: usize) -> BoxFuture<'_, Result<Vec<u8>, QrngError>> {
+        Box::pin(async move { Ok(vec![self.0; size]) })
+    }
+}
+
+#[tokio::test]
+async fn test_entropy_from_custom_source() {
+    let app = router_with_source(DeviceManager::new(), Arc::new(FixedSource(0x5a)), ServerConfig::default());
+    let (status, body) = get(app.clone(), "/entropy?bytes=16").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, vec![0x5a; 16]);
+
+    // Naming a serial still goes to the manager
+    let (status, _) = get(app.clone(), "/entropy?bytes=16&serial=NOPE").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, body) = get(app, "/metrics").await;
+    let body = String::from_utf8(body).unwrap();
+    assert!(body.contains("qrng_device_reads_total{serial=\"source\"} 1"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_stream_sends_entropy_frames() {
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = router_with_source(DeviceManager::new(), Arc::new(FixedSource(0x5a)), ServerConfig::default());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/stream", addr)).await
+        .expect("Failed to connect");
+    for _ in 0..3 {
+        match socket.next().await {
+            Some(Ok(Message::Binary(frame))) => assert_eq!(frame.to_vec(), vec![0x5a; DEFAULT_STREAM_CHUNK]),
+            other => panic!("Expected an entropy frame, got {:?}", other),
+        }
+    }
+    socket.send(Message::Ping(b"still there?".to_vec().into())).await.unwrap();
+    socket.close(None).await.expect("Failed to close");
+
+    // Frame size is taken from the query within bounds
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/stream?chunk=32", addr)).await
+        .expect("Failed to connect");
+    match socket.next().await {
+        Some(Ok(Message::Binary(frame))) => assert_eq!(frame.len(), 32),
+        other => panic!("Expected an entropy frame, got {:?}", other),
+    }
+    socket.close(None).await.expect("Failed to close");
+
+    let request = format!("ws://{}/stream?chunk={}", addr, MAX_STREAM_CHUNK + 1);
+    assert!(tokio_tungstenite::connect_async(request).await.is_err());
+}
+
+#[tokio::test]
+async fn test_metrics_count_served_bytes() {
+    let app = router_with_source(DeviceManager::new(), Arc::new(FixedSource(0x5a)), ServerConfig::default());
+    for _ in 0..2 {
+        let (status, _) = get(app.clone(), "/entropy?bytes=24").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (_, body) = get(app, "/metrics").await;
+    let body = String::from_utf8(body).unwrap();
+    assert!(body.contains("qrng_bytes_served_total 48"), "{}", body);
+    assert!(body.contains("qrng_device_bytes_served_total{serial=\"source\"} 48"), "{}", body);
+    assert!(body.contains("qrng_device_read_duration_seconds_count{serial=\"source\"} 2"), "{}", body);
+}
+
+#[derive(Debug)]
+struct CountingSource;
+
+impl EntropySource for CountingSource {
+    fn read(&self, size: usize) -> BoxFuture<'_, Result<Vec<u8>, QrngError>> {
+        Box::pin(async move { Ok((0..size).map(|i| i as u8).collect()) })
+    }
+}
+
+async fn get_response(app: Router, uri: &str) -> (StatusCode, Option<String>, Vec<u8>) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.expect("Failed to send request");
+    let status = response.status();
+    let content_type = response.headers().get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+    let body = to_bytes(response.into_body(), usize::MAX).await.expect("Failed to read body");
+    (status, content_type, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_entropy_encodings() {
+    let app = router_with_source(DeviceManager::new(), Arc::new(CountingSource), ServerConfig::default());
+    let expected: Vec<u8> = (0..300).map(|i| i as u8).collect();
+
+    for uri in ["/entropy?bytes=300", "/entropy?bytes=300&encoding=raw"] {
+        let (status, content_type, body) = get_response(app.clone(), uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type.as_deref(), Some("application/octet-stream"));
+        assert_eq!(body, expected);
+    }
+
+    let (status, content_type, body) = get_response(app.clone(), "/entropy?bytes=300&encoding=hex").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/plain"));
+    let decoded: Vec<u8> = body.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+        .collect();
+    assert_eq!(decoded, expected);
+
+    let (status, content_type, body) = get_response(app.clone(), "/entropy?bytes=300&encoding=base64").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/plain"));
+    assert_eq!(BASE64.decode(body).expect("Failed to decode base64"), expected);
+
+    let (status, _, _) = get_response(app, "/entropy?bytes=300&encoding=rot13").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_status_of_unknown_device() {
+    let app = router(DeviceManager::new(), ServerConfig::default());
+    let (status, body) = get(app, "/devices/NOPE/status").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(String::from_utf8(body).unwrap().contains("NOPE"));
+}
+
+struct TestPki {
+    dir: tempfile::TempDir,
+    ca: rcgen::Certificate,
+    ca_key: rcgen::KeyPair,
+}
+
+impl TestPki {
+    fn new() -> Self {
+        let ca_key = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = params.self_signed(&ca_key).unwrap();
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        std::fs::write(dir.path().join("ca.pem"), ca.pem()).unwrap();
+        Self { dir, ca, ca_key }
+    }
+
+    fn path(&self, name: &str) -> std::path::PathBuf {
+        self.dir.path().join(name)
+    }
+
+    // Issue a certificate for localhost, writing `<name>.pem` and `<name>.key`
+    fn issue(&self, name: &str) -> (rustls_pki_types::CertificateDer<'static>, rustls_pki_types::PrivateKeyDer<'static>) {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let params = rcgen::CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+        let cert = params.signed_by(&key, &self.ca, &self.ca_key).unwrap();
+        std::fs::write(self.path(&format!("{}.pem", name)), cert.pem()).unwrap();
+        std::fs::write(self.path(&format!("{}.key", name)), key.serialize_pem()).unwrap();
+        let der_key = rustls_pki_types::PrivatePkcs8KeyDer::from(key.serialize_der()).into();
+        (cert.der().clone(), der_key)
+    }
+}
+
+async fn start_tls_server(pki: &TestPki, client_ca: Option<&std::path::Path>) -> SocketAddr {
+    pki.issue("server");
+    let tls = tls_config(&pki.path("server.pem"), &pki.path("server.key"), client_ca).expect("Failed to load TLS config");
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(tls::serve_tls_on(listener, tls, router(DeviceManager::new(), ServerConfig::default()), std::future::pending()));
+    addr
+}
+
+async fn https_get(pki: &TestPki, addr: SocketAddr, client_cert: Option<&str>, path: &str) -> std::io::Result<String> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(pki.ca.der().clone()).unwrap();
+    let builder = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots);
+    let config = match client_cert {
+        Some(name) => {
+            let (cert, key) = pki.issue(name);
+            builder.with_client_auth_cert(vec![cert], key).unwrap()
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let connector = tokio_rustls::TlsConnector::from(Arc::new(config));
+    let stream = tokio::net::TcpStream::connect(addr).await?;
+    let server_name = rustls_pki_types::ServerName::try_from("localhost").unwrap();
+    let mut stream = connector.connect(server_name, stream).await?;
+    let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+#[tokio::test]
+async fn test_serve_tls() {
+    let pki = TestPki::new();
+    let addr = start_tls_server(&pki, None).await;
+
+    let response = https_get(&pki, addr, None, "/devices").await.expect("Failed to send HTTPS request");
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "unexpected response: {}", response);
+    assert!(response.ends_with("[]"));
+}
+
+#[tokio::test]
+async fn test_serve_tls_requires_client_cert() {
+    let pki = TestPki::new();
+    let addr = start_tls_server(&pki, Some(&pki.path("ca.pem"))).await;
+
+    let response = https_get(&pki, addr, None, "/devices").await;
+    assert!(response.is_err(), "served a client without a certificate: {:?}", response);
+
+    let response = https_get(&pki, addr, Some("client"), "/devices").await.expect("Failed to send HTTPS request");
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "unexpected response: {}", response);
+}
+
+#[tokio::test]
+async fn test_serve_with_tls_config() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let pki = TestPki::new();
+    pki.issue("server");
+    let bind_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let config = ServerConfig {
+        bind_addr,
+        tls: Some(TlsConfig::new(pki.path("server.pem"), pki.path("server.key"))),
+        ..ServerConfig::default()
+    };
+    tokio::spawn(serve(DeviceManager::new(), config));
+
+    // Retry while the server starts listening
+    let mut response = https_get(&pki, bind_addr, None, "/entropy?bytes=16").await;
+    for _ in 0..50 {
+        if response.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        response = https_get(&pki, bind_addr, None, "/entropy?bytes=16").await;
+    }
+    let response = response.expect("Failed to send HTTPS request");
+
+    // No devices are attached, so the request reaches the handler and 404s
+    assert!(response.starts_with("HTTP/1.1 404"), "unexpected response: {}", response);
+
+    // Plaintext requests get no HTTP response
+    let mut stream = tokio::net::TcpStream::connect(bind_addr).await.unwrap();
+    stream.write_all(b"GET /devices HTTP/1.1\r\nHost: localhost\r\n\r\n").await.unwrap();
+    let mut plain = Vec::new();
+    let _ = stream.read_to_end(&mut plain).await;
+    assert!(!plain.starts_with(b"HTTP/1.1"), "served plaintext: {:?}", String::from_utf8_lossy(&plain));
+}
+
+#[test]
+fn test_tls_config_rejects_bad_pem() {
+    let pki = TestPki::new();
+    pki.issue("server");
+    std::fs::write(pki.path("garbage.pem"), "not a certificate").unwrap();
+
+    let result = tls_config(&pki.path("garbage.pem"), &pki.path("server.key"), None);
+    assert!(matches!(result.unwrap_err(), QrngError::TlsError(_)));
+    let result = tls_config(&pki.path("server.pem"), &pki.path("missing.key"), None);
+    assert!(matches!(result.unwrap_err(), QrngError::TlsError(_)));
+}
+
+#[tokio::test]
+async fn test_metrics_count_failed_reads() {
+    let app = router(DeviceManager::new(), ServerConfig::default());
+    let (status, _) = get(app.clone(), "/entropy?bytes=32").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = get(app, "/metrics").await;
+    assert_eq!(status, StatusCode::OK);
+    let body = String::from_utf8(body).unwrap();
+    assert!(body.contains("qrng_bytes_served_total 0"), "{}", body);
+    assert!(body.contains("qrng_device_read_errors_total{serial=\"unknown\"} 1"), "{}", body);
+}
+
+#[test]
+fn test_metrics_record_reads_and_status() {
+    let metrics = Metrics::new();
+    metrics.record_read("MOCK-A", 32, Duration::from_millis(2));
+    metrics.record_read("MOCK-A", 16, Duration::from_millis(1));
+    metrics.record_read("MOCK-B", 8, Duration::from_millis(1));
+    metrics.record_status("MOCK-A", 36.5, 4.75);
+
+    let body = metrics.encode();
+    assert!(body.contains("qrng_bytes_served_total 56"), "{}", body);
+    assert!(body.contains("qrng_device_reads_total{serial=\"MOCK-A\"} 2"), "{}", body);
+    assert!(body.contains("qrng_device_reads_total{serial=\"MOCK-B\"} 1"), "{}", body);
+    assert!(body.contains("qrng_device_bytes_served_total{serial=\"MOCK-A\"} 48"), "{}", body);
+    assert!(body.contains("qrng_device_read_duration_seconds_count{serial=\"MOCK-A\"} 2"), "{}", body);
+    assert!(body.contains("qrng_device_temperature{serial=\"MOCK-A\"} 36.5"), "{}", body);
+    assert!(body.contains("qrng_device_voltage{serial=\"MOCK-A\"} 4.75"), "{}", body);
+}
+
+async fn grpc_client(addr: SocketAddr) -> proto::quantum_leaks_client::QuantumLeaksClient<tonic::transport::Channel> {
+    // Retry while the server starts listening
+    for _ in 0..50 {
+        if let Ok(client) = proto::quantum_leaks_client::QuantumLeaksClient::connect(format!("http://{}", addr)).await {
+            return client;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Failed to connect to the gRPC server on {}", addr);
+}
+
+#[tokio::test]
+async fn test_grpc_served_alongside_rest() {
+    use futures::StreamExt;
+    use proto::{GetEntropyRequest, GetStatusRequest, ListDevicesRequest};
+
+    let manager = DeviceManager::new();
+    manager.add_mock("MOCK-B", 2).await.unwrap();
+    manager.add_mock("MOCK-A", 1).await.unwrap();
+    let grpc_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let config = ServerConfig {
+        bind_addr: "127.0.0.1:0".parse().unwrap(),
+        grpc_addr: Some(grpc_addr),
+        max_entropy_bytes: 4 * GRPC_CHUNK,
+        ..ServerConfig::default()
+    };
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(serve_with_shutdown(manager, config, async {
+        let _ = stopped.await;
+    }));
+    let mut client = grpc_client(grpc_addr).await;
+
+    let devices = client.list_devices(ListDevicesRequest {}).await.expect("Failed to list devices");
+    assert_eq!(devices.into_inner().serials, ["MOCK-A", "MOCK-B"]);
+
+    // Large requests arrive in chunks
+    let request = GetEntropyRequest { serial: Some("MOCK-A".to_string()), bytes: GRPC_CHUNK as u64 + 100 };
+    let chunks: Vec<_> = client.get_entropy(request).await.expect("Failed to get entropy").into_inner().collect().await;
+    let sizes: Vec<usize> = chunks.into_iter().map(|chunk| chunk.expect("Stream failed").data.len()).collect();
+    assert_eq!(sizes, [GRPC_CHUNK, 100]);
+
+    let request = GetEntropyRequest { serial: None, bytes: 4 * GRPC_CHUNK as u64 + 1 };
+    assert_eq!(client.get_entropy(request).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    let request = GetStatusRequest { serial: "NOPE".to_string() };
+    assert_eq!(client.get_status(request).await.unwrap_err().code(), tonic::Code::NotFound);
+
+    // Both servers stop on the one signal
+    stop.send(()).unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(5), server).await
+        .expect("Servers didn't stop after the shutdown signal");
+    result.unwrap().expect("Server failed");
+}
+
+#[tokio::test]
+async fn test_grpc_requires_api_key() {
+    use proto::ListDevicesRequest;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let config = ServerConfig { api_keys: ApiKeys::new(["first-key"]), ..ServerConfig::default() };
+    let routes = grpc_routes(DeviceManager::new(), config);
+    tokio::spawn(tonic::transport::Server::builder()
+        .add_routes(routes)
+        .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)));
+    let mut client = grpc_client(addr).await;
+
+    let result = client.list_devices(ListDevicesRequest {}).await;
+    assert_eq!(result.unwrap_err().code(), tonic::Code::Unauthenticated);
+
+    let mut request = tonic::Request::new(ListDevicesRequest {});
+    request.metadata_mut().insert("authorization", "Bearer first-key".parse().unwrap());
+    assert!(client.list_devices(request).await.expect("Key was refused").into_inner().serials.is_empty());
+}
+
+#[tokio::test]
+async fn test_grpc_read_and_stream_entropy() {
+    use futures::StreamExt;
+    use proto::{ListDevicesRequest, ReadEntropyRequest, StreamEntropyRequest};
+
+    let manager = DeviceManager::new();
+    manager.add_mock("MOCK-A", 1).await.unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(tonic::transport::Server::builder()
+        .add_routes(grpc_routes(manager, ServerConfig::default()))
+        .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)));
+    let mut client = grpc_client(addr).await;
+
+    let request = ReadEntropyRequest { serial: None, size: 100 };
+    let chunk = client.read_entropy(request).await.expect("Failed to read entropy").into_inner();
+    assert_eq!(chunk.data.len(), 100);
+    let request = ReadEntropyRequest { serial: Some("NOPE".to_string()), size: 100 };
+    assert_eq!(client.read_entropy(request).await.unwrap_err().code(), tonic::Code::NotFound);
+
+    // Chunks keep coming until the client hangs up
+    let request = StreamEntropyRequest { serial: Some("MOCK-A".to_string()), chunk_size: 32 };
+    let mut chunks = client.stream_entropy(request).await.expect("Failed to stream entropy").into_inner();
+    for _ in 0..3 {
+        let chunk = chunks.next().await.expect("Stream ended").expect("Stream failed");
+        assert_eq!(chunk.data.len(), 32);
+    }
+    drop(chunks);
+    let request = StreamEntropyRequest { serial: None, chunk_size: MAX_STREAM_CHUNK as u64 + 1 };
+    assert_eq!(client.stream_entropy(request).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+    let devices = client.list_devices(ListDevicesRequest {}).await.expect("Failed to list devices").into_inner();
+    assert_eq!(devices.serials, ["MOCK-A"]);
+    let info = &devices.devices[0];
+    assert_eq!((info.serial.as_str(), info.vendor_id, info.initialized), ("MOCK-A", 0x0403, true));
+}
+
+#[tokio::test]
+async fn test_grpc_unhealthy_device_is_unavailable() {
+    use proto::ReadEntropyRequest;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(tonic::transport::Server::builder()
+        .add_routes(grpc_routes(failing_health_manager().await, ServerConfig::default()))
+        .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)));
+    let mut client = grpc_client(addr).await;
+
+    let request = ReadEntropyRequest { serial: Some("MOCK-A".to_string()), size: 32 };
+    assert_eq!(client.read_entropy(request).await.unwrap_err().code(), tonic::Code::Unavailable);
+}
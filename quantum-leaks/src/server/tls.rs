@@ -0,0 +1,122 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use axum_server::Handle;
+use feed_me_bits::device::DeviceManager;
+use feed_me_bits::QrngError;
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use tracing::info;
+use super::{serve, ServerConfig};
+
+/// PEM files for serving HTTPS.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// Require client certificates signed by a CA in this file (mutual TLS).
+    pub client_ca_path: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        Self { cert_path: cert_path.into(), key_path: key_path.into(), client_ca_path: None }
+    }
+
+    pub(crate) fn load(&self) -> Result<Arc<rustls::ServerConfig>, QrngError> {
+        tls_config(&self.cert_path, &self.key_path, self.client_ca_path.as_deref())
+    }
+}
+
+fn tls_error(e: impl std::fmt::Display) -> QrngError {
+    QrngError::TlsError(e.to_string())
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, QrngError> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| QrngError::TlsError(format!("Failed to load certificates from {}: {}", path.display(), e)))?;
+    if certs.is_empty() {
+        return Err(QrngError::TlsError(format!("No certificates in {}", path.display())));
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>, QrngError> {
+    PrivateKeyDer::from_pem_file(path)
+        .map_err(|e| QrngError::TlsError(format!("Failed to load private key from {}: {}", path.display(), e)))
+}
+
+/// Build a rustls server config from PEM files. With `client_ca_path` set,
+/// clients must present a certificate signed by one of the CAs in it.
+pub fn tls_config(cert_path: &Path, key_path: &Path, client_ca_path: Option<&Path>) -> Result<Arc<rustls::ServerConfig>, QrngError> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(tls_error)?;
+
+    let builder = match client_ca_path {
+        Some(path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(path)? {
+                roots.add(cert).map_err(tls_error)?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(tls_error)?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(load_certs(cert_path)?, load_key(key_path)?)
+        .map_err(tls_error)?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+/// Serve the entropy API over HTTPS on `addr` until the process exits,
+/// overriding the address and TLS settings in `config`. Passing
+/// `client_ca_path` turns on mutual TLS.
+pub async fn serve_tls(
+    addr: SocketAddr,
+    cert_path: &Path,
+    key_path: &Path,
+    client_ca_path: Option<&Path>,
+    manager: DeviceManager,
+    config: ServerConfig,
+) -> Result<(), QrngError> {
+    let tls = TlsConfig {
+        cert_path: cert_path.to_path_buf(),
+        key_path: key_path.to_path_buf(),
+        client_ca_path: client_ca_path.map(Path::to_path_buf),
+    };
+    serve(manager, ServerConfig { bind_addr: addr, tls: Some(tls), ..config }).await
+}
+
+pub(crate) async fn serve_tls_on(
+    listener: std::net::TcpListener,
+    tls: Arc<rustls::ServerConfig>,
+    app: Router,
+    signal: impl Future<Output = ()> + Send + 'static,
+) -> Result<(), QrngError> {
+    listener.set_nonblocking(true)?;
+    info!("Serving entropy on https://{}", listener.local_addr()?);
+    let handle = Handle::new();
+    let shutdown = handle.clone();
+    tokio::spawn(async move {
+        signal.await;
+        shutdown.graceful_shutdown(None);
+    });
+    axum_server::from_tcp_rustls(listener, RustlsConfig::from_config(tls))
+        .handle(handle)
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+use super::*;
+
+fn parse(args: &[&str]) -> Option<Command> {
+    Cli::try_parse_from(std::iter::once("quantum-leaks").chain(args.iter().copied()))
+        .expect("Failed to parse arguments")
+        .command
+}
+
+#[test]
+fn test_parse_log_format() {
+    let cli = Cli::try_parse_from(["quantum-leaks", "scan", "--log-format", "json"]).expect("Failed to parse arguments");
+    assert_eq!(cli.log_format, LogFormat::Json);
+    assert!(Cli::try_parse_from(["quantum-leaks", "--log-format", "xml"]).is_err());
+}
+
+#[test]
+fn test_parse_subcommands() {
+    assert!(parse(&[]).is_none());
+    assert!(matches!(
+        parse(&["serve", "--addr", "0.0.0.0:9000", "--rate-limit", "512"]),
+        Some(Command::Serve { addr: Some(addr), rate_limit: Some(512.0), burst: None, global_rate_limit: None, .. })
+            if addr.port() == 9000
+    ));
+    assert!(matches!(
+        parse(&["serve", "--grpc-addr", "127.0.0.1:50051"]),
+        Some(Command::Serve { addr: None, grpc_addr: Some(grpc_addr), egd_socket: None, .. }) if grpc_addr.port() == 50051
+    ));
+    assert!(matches!(
+        parse(&["serve", "--egd-socket", "/run/egd-pool"]),
+        Some(Command::Serve { egd_socket: Some(path), .. }) if path.to_str() == Some("/run/egd-pool")
+    ));
+    assert!(matches!(parse(&["list"]), Some(Command::Scan { json: false, include_status: false })));
+    assert!(matches!(parse(&["scan", "--json"]), Some(Command::Scan { json: true, .. })));
+    assert!(matches!(
+        parse(&["read", "--serial", "QWR4A003", "--bytes", "64", "--out", "entropy.bin"]),
+        Some(Command::Read { serial: Some(serial), bytes: 64, out: Some(out) })
+            if serial == "QWR4A003" && out.to_str() == Some("entropy.bin")
+    ));
+    assert!(matches!(parse(&["read", "--bytes", "64"]), Some(Command::Read { serial: None, out: None, .. })));
+    assert!(matches!(parse(&["status", "--serial", "QWR4A003"]), Some(Command::Status { serial }) if serial == "QWR4A003"));
+    assert!(matches!(parse(&["bench"]), Some(Command::Bench { serial: None, seconds: 5 })));
+    assert!(matches!(parse(&["watch", "--serial", "QWR4A003"]), Some(Command::Watch { interval: 1000, .. })));
+    assert!(matches!(
+        parse(&["loadtest", "--concurrency", "16", "--sizes", "32,32,4096", "--seconds", "10"]),
+        Some(Command::Loadtest { concurrency: 16, seconds: 10, stall_timeout: 5000, sizes, .. }) if sizes == [32, 32, 4096]
+    ));
+    assert!(matches!(parse(&["stream", "--total-bytes", "10"]), Some(Command::Stream { total_bytes: Some(10), chunk_size: 4096, .. })));
+}
+
+#[test]
+fn test_parse_rejects_bad_arguments() {
+    let try_parse = |args: &[&str]| Cli::try_parse_from(std::iter::once("quantum-leaks").chain(args.iter().copied()));
+    assert!(try_parse(&["read"]).is_err());
+    assert!(try_parse(&["status"]).is_err());
+    assert!(try_parse(&["watch", "--serial", "QWR4A003", "--interval", "0"]).is_err());
+    assert!(try_parse(&["serve", "--addr", "not-an-address"]).is_err());
+    assert!(try_parse(&["serve", "--burst", "64"]).is_err());
+    for rate in ["0", "-5", "NaN", "inf", "fast"] {
+        assert!(try_parse(&["serve", "--rate-limit", rate, "--burst", "64"]).is_err(), "{}", rate);
+        assert!(try_parse(&["serve", "--global-rate-limit", rate]).is_err(), "{}", rate);
+    }
+    assert!(try_parse(&["loadtest", "--concurrency", "0"]).is_err());
+    assert!(try_parse(&["loadtest", "--sizes", "32,lots"]).is_err());
+}